@@ -0,0 +1,142 @@
+use crate::bevy::prelude::*;
+use crate::{RngComponent, WeightedTable};
+use std::marker::PhantomData;
+
+/// The `AnimationVariants` component lists the animation clips an entity can
+/// play in each of its states, e.g. several idle animations.
+///
+/// The state is a component of type `S` on the same entity. Whenever it
+/// changes to a different value, the [`AnimationVariantPlugin`] picks one of
+/// the clips of the new state, by weight, and stores it in the
+/// [`SelectedAnimation`] component:
+///
+/// ```rust,ignore
+/// let variants = AnimationVariants::new()
+///     .with_state(Pose::Idle, vec![(idle, 8.0), (stretch, 1.0), (yawn, 1.0)])
+///     .with_state(Pose::Walk, vec![(walk, 1.0)]);
+///
+/// commands.spawn_bundle((Pose::Idle, variants, StableId::from_name("villager 3"), NeedsRng));
+///
+/// app.add_plugin(AnimationVariantPlugin::<Pose, Handle<AnimationClip>>::default());
+/// ```
+///
+/// The clips are picked with the [`RngComponent`] of the entity, see
+/// [`RngComponentPlugin`](crate::RngComponentPlugin), so every entity makes
+/// the same choices in replays and on every networked client, no matter how
+/// many other entities change state in the same frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationVariants<S, C> {
+    states: Vec<(S, WeightedTable<C>)>,
+}
+
+impl<S, C> Default for AnimationVariants<S, C> {
+    fn default() -> Self {
+        Self { states: vec![] }
+    }
+}
+
+impl<S: PartialEq, C> AnimationVariants<S, C> {
+    /// Create variants without any states.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the weighted clips of the given state.
+    pub fn with_state(mut self, state: S, clips: impl IntoIterator<Item = (C, f32)>) -> Self {
+        self.set_state(state, clips.into_iter().collect());
+        self
+    }
+
+    /// Set the weighted clips of the given state.
+    pub fn set_state(&mut self, state: S, clips: WeightedTable<C>) {
+        match self.states.iter_mut().find(|(other, _)| *other == state) {
+            Some((_, table)) => *table = clips,
+            None => self.states.push((state, clips)),
+        }
+    }
+
+    /// Returns the weighted clips of the given state.
+    pub fn get(&self, state: &S) -> Option<&WeightedTable<C>> {
+        self.states
+            .iter()
+            .find(|(other, _)| other == state)
+            .map(|(_, table)| table)
+    }
+}
+
+/// The `SelectedAnimation` component holds the clip picked for the current
+/// state of an entity, see [`AnimationVariants`].
+///
+/// Remove it to pick a new clip for the current state, e.g. once a one-off
+/// idle animation finished playing. It is removed when the entity enters a
+/// state without clips.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectedAnimation<S, C> {
+    /// The state the clip was picked for.
+    pub state: S,
+
+    /// The clip to play.
+    pub clip: C,
+}
+
+/// Picks the animation clip of every entity entering a new state, see
+/// [`AnimationVariants`].
+///
+/// Add it after the [`RngComponentPlugin`](crate::RngComponentPlugin), for
+/// entities to receive their rng.
+pub struct AnimationVariantPlugin<S, C> {
+    marker: PhantomData<fn() -> (S, C)>,
+}
+
+impl<S, C> Default for AnimationVariantPlugin<S, C> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S, C> Plugin for AnimationVariantPlugin<S, C>
+where
+    S: Clone + PartialEq + Send + Sync + 'static,
+    C: Clone + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(select_animations::<S, C>.system());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn select_animations<S, C>(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &S,
+        &AnimationVariants<S, C>,
+        Option<&SelectedAnimation<S, C>>,
+        &mut RngComponent,
+    )>,
+) where
+    S: Clone + PartialEq + Send + Sync + 'static,
+    C: Clone + Send + Sync + 'static,
+{
+    for (entity, state, variants, selected, mut rng) in query.iter_mut() {
+        if selected.map_or(false, |selected| selected.state == *state) {
+            continue;
+        }
+
+        let mut commands = commands.entity(entity);
+        match variants.get(state).and_then(|clips| clips.pick(&mut **rng)) {
+            Some(clip) => {
+                commands.insert(SelectedAnimation {
+                    state: state.clone(),
+                    clip: clip.clone(),
+                });
+            }
+            None if selected.is_some() => {
+                commands.remove::<SelectedAnimation<S, C>>();
+            }
+            None => {}
+        }
+    }
+}
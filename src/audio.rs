@@ -0,0 +1,321 @@
+use crate::bevy::prelude::*;
+use crate::{RngComponent, RngIndexExt};
+use rand::Rng;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// Helpers to vary sound effects.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+pub trait RngAudioExt: Rng {
+    /// Returns a playback rate within the range, e.g. `0.95..1.05`.
+    ///
+    /// Rates are evenly distributed in octaves rather than linearly, so a
+    /// range like `0.5..2.0` lowers the pitch as often as it raises it. An
+    /// empty range always produces its start. Exactly one value is drawn.
+    fn pitch(&mut self, range: Range<f32>) -> f32 {
+        let t: f32 = self.gen();
+        if range.start < range.end && range.start > 0.0 {
+            let (low, high) = (range.start.log2(), range.end.log2());
+            (low + (high - low) * t).exp2()
+        } else {
+            range.start
+        }
+    }
+
+    /// Returns a volume within the range, e.g. `0.8..1.0`.
+    ///
+    /// An empty range always produces its start. Exactly one value is drawn.
+    fn volume(&mut self, range: Range<f32>) -> f32 {
+        let t: f32 = self.gen();
+        if range.start < range.end {
+            range.start + (range.end - range.start) * t
+        } else {
+            range.start
+        }
+    }
+}
+
+impl<R: Rng + ?Sized> RngAudioExt for R {}
+
+/// The pitch and volume to play a sound with, see [`RandomPitch::roll`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundVariation {
+    /// The playback rate, `1.0` being the original pitch.
+    pub pitch: f32,
+
+    /// The volume, `1.0` being the original volume.
+    pub volume: f32,
+}
+
+/// The `RandomPitch` component jitters the pitch and volume of the sounds an
+/// entity plays, so repeated effects like footsteps don't sound mechanical.
+///
+/// ```rust,ignore
+/// let variation = footsteps.roll(&mut rng);
+/// // Play the clip at `variation.pitch` and `variation.volume`...
+/// ```
+///
+/// The component only picks the values; pass them to the audio backend of
+/// your choice when playing the sound. To also pick the clip, and have the
+/// values picked whenever the entity plays a sound, use a [`RandomSample`]
+/// with the [`RandomSamplePlugin`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomPitch {
+    /// The range of playback rates, see [`RngAudioExt::pitch`].
+    pub pitch: Range<f32>,
+
+    /// The range of volumes, see [`RngAudioExt::volume`].
+    pub volume: Range<f32>,
+}
+
+impl RandomPitch {
+    /// Jitter the pitch within the given range, at the original volume.
+    pub fn new(pitch: Range<f32>) -> Self {
+        Self {
+            pitch,
+            volume: 1.0..1.0,
+        }
+    }
+
+    /// Jitter the volume within the given range.
+    pub fn with_volume(mut self, volume: Range<f32>) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Roll the pitch and volume of the next sound, drawing exactly two
+    /// values.
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> SoundVariation {
+        SoundVariation {
+            pitch: rng.pitch(self.pitch.clone()),
+            volume: rng.volume(self.volume.clone()),
+        }
+    }
+}
+
+impl Default for RandomPitch {
+    fn default() -> Self {
+        Self::new(1.0..1.0)
+    }
+}
+
+/// A clip picked by [`RandomSample::play`], and how to play it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedSample<C> {
+    /// The clip to play.
+    pub clip: C,
+
+    /// The pitch and volume to play the clip with.
+    pub variation: SoundVariation,
+}
+
+/// The `RandomSample` component picks a random clip from a set each time a
+/// sound is played, and jitters its pitch and volume.
+///
+/// The clips can be of any type, typically a `Handle` to an audio asset:
+///
+/// ```rust,ignore
+/// commands.spawn_bundle((
+///     Player,
+///     RandomSample::new(vec![step_1, step_2, step_3])
+///         .with_jitter(RandomPitch::new(0.95..1.05).with_volume(0.8..1.0)),
+/// ));
+///
+/// fn footsteps(mut rng: Local<Rng>, mut players: Query<&mut RandomSample<Handle<AudioSource>>>) {
+///     for mut steps in players.iter_mut() {
+///         if let Some(sample) = steps.play(&mut *rng) {
+///             // Play `sample.clip` with `sample.variation`...
+///         }
+///     }
+/// }
+/// ```
+///
+/// The same clip is never picked twice in a row, unless it is the only one.
+/// The [`RandomSamplePlugin`] calls [`play`](RandomSample::play) for you
+/// whenever the entity is asked to play a sound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomSample<C> {
+    /// The pitch and volume jitter applied to every clip.
+    pub jitter: RandomPitch,
+
+    clips: Vec<C>,
+    last: Option<usize>,
+}
+
+impl<C: Clone> RandomSample<C> {
+    /// Pick from the given clips, without jitter.
+    pub fn new(clips: Vec<C>) -> Self {
+        Self {
+            jitter: RandomPitch::default(),
+            clips,
+            last: None,
+        }
+    }
+
+    /// Jitter the pitch and volume of every clip.
+    pub fn with_jitter(mut self, jitter: RandomPitch) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The clips to pick from.
+    pub fn clips(&self) -> &[C] {
+        &self.clips
+    }
+
+    /// Pick the clip to play next, and its pitch and volume, or `None` if
+    /// there are no clips.
+    pub fn play<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<PlayedSample<C>> {
+        // Pick among the other clips, skipping over the last one.
+        let index = match self.last {
+            Some(last) if self.clips.len() > 1 => {
+                let index = rng.index(self.clips.len() - 1)?;
+                if index >= last {
+                    index + 1
+                } else {
+                    index
+                }
+            }
+            _ => rng.index(self.clips.len())?,
+        };
+
+        self.last = Some(index);
+
+        Some(PlayedSample {
+            clip: self.clips[index].clone(),
+            variation: self.jitter.roll(rng),
+        })
+    }
+}
+
+/// The event asking an entity with a [`RandomSample`] to play a sound, see
+/// [`RandomSamplePlugin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlaySample {
+    /// The entity playing the sound.
+    pub entity: Entity,
+}
+
+/// The event sent by the [`RandomSamplePlugin`] with the clip an entity
+/// plays, and its pitch and volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplePlayed<C> {
+    /// The entity playing the sound.
+    pub entity: Entity,
+
+    /// The clip to play, and how to play it.
+    pub sample: PlayedSample<C>,
+}
+
+/// Picks a clip and its jitter whenever an entity plays a sound, see
+/// [`RandomSample`].
+///
+/// Send a [`PlaySample`] event for the entity, and the plugin answers with a
+/// [`SamplePlayed`] event once the clip is picked:
+///
+/// ```rust,ignore
+/// app.add_plugin(RandomSamplePlugin::<Handle<AudioSource>>::default());
+///
+/// fn step(players: Query<Entity, With<Player>>, mut sounds: EventWriter<PlaySample>) {
+///     for entity in players.iter() {
+///         sounds.send(PlaySample { entity });
+///     }
+/// }
+///
+/// fn play(mut played: EventReader<SamplePlayed<Handle<AudioSource>>>) {
+///     for played in played.iter() {
+///         // Play `played.sample.clip` with `played.sample.variation`...
+///     }
+/// }
+/// ```
+///
+/// Bevy's own `Audio` resource can't change the pitch or volume of a sound,
+/// so the plugin doesn't play anything itself: hand the event to the audio
+/// backend of your choice.
+///
+/// The clips are picked with the [`RngComponent`] of the entity, see
+/// [`RngComponentPlugin`](crate::RngComponentPlugin), so every entity plays
+/// the same sounds in replays. Add it after that plugin; requests for
+/// entities without a `RandomSample` of this clip type or without an rng are
+/// ignored.
+pub struct RandomSamplePlugin<C> {
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for RandomSamplePlugin<C> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C> Plugin for RandomSamplePlugin<C>
+where
+    C: Clone + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<PlaySample>()
+            .add_event::<SamplePlayed<C>>()
+            .add_system(play_samples::<C>.system());
+    }
+}
+
+fn play_samples<C>(
+    mut requests: EventReader<PlaySample>,
+    mut samples: Query<(&mut RandomSample<C>, &mut RngComponent)>,
+    mut played: EventWriter<SamplePlayed<C>>,
+) where
+    C: Clone + Send + Sync + 'static,
+{
+    for request in requests.iter() {
+        if let Ok((mut clips, mut rng)) = samples.get_mut(request.entity) {
+            if let Some(sample) = clips.play(&mut **rng) {
+                played.send(SamplePlayed {
+                    entity: request.entity,
+                    sample,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let jitter = RandomPitch::new(0.5..2.0).with_volume(0.8..1.0);
+        for _ in 0..1000 {
+            let variation = jitter.roll(&mut rng);
+            assert!((0.5..=2.0).contains(&variation.pitch));
+            assert!((0.8..=1.0).contains(&variation.volume));
+        }
+
+        assert_eq!(rng.pitch(1.2..1.2), 1.2);
+        assert_eq!(rng.volume(0.5..0.1), 0.5);
+    }
+
+    #[test]
+    fn never_plays_a_clip_twice_in_a_row() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2);
+        let mut samples = RandomSample::new(vec!["a", "b", "c"]);
+        let mut last = None;
+        for _ in 0..1000 {
+            let clip = samples.play(&mut rng).unwrap().clip;
+            assert_ne!(Some(clip), last);
+            last = Some(clip);
+        }
+
+        let mut single = RandomSample::new(vec!["a"]);
+        assert_eq!(single.play(&mut rng).unwrap().clip, "a");
+        assert_eq!(single.play(&mut rng).unwrap().clip, "a");
+        assert_eq!(RandomSample::<&str>::new(vec![]).play(&mut rng), None);
+    }
+}
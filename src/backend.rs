@@ -0,0 +1,255 @@
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use rand_xoshiro::{SplitMix64, Xoshiro256StarStar};
+
+/// The characteristic polynomial of the xoshiro256 state transition, without
+/// its `x^256` term, as the coefficients of `x^0` to `x^255`.
+///
+/// `x^(2^128)` modulo this polynomial is the `jump` constant of xoshiro256.
+const XOSHIRO_POLYNOMIAL: [u64; 4] = [
+    0x9d11_6f2b_b0f0_f001,
+    0x0280_002b_cefd_1a5e,
+    0x04b4_edcf_2625_9f85,
+    0x0003_c03c_3f3e_cb19,
+];
+
+/// The algorithm generating the values of an [`Rng`](crate::Rng).
+///
+/// Seeds and streams are derived the same way for every backend, but the
+/// values drawn from them differ, so switching backends changes all random
+/// output of an app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backend {
+    /// xoshiro256**, a fast generator with a small state. This is the
+    /// default.
+    Xoshiro256StarStar,
+
+    /// PCG64 (`Lcg128Xsl64`), with a larger period and better statistical
+    /// quality for the least significant bits, at a slightly higher cost.
+    Pcg64,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::Xoshiro256StarStar
+    }
+}
+
+/// The generator of an [`Rng`](crate::Rng), for one of the [`Backend`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Generator {
+    Xoshiro256StarStar(Xoshiro256StarStar),
+    Pcg64(Pcg64),
+}
+
+impl Generator {
+    /// Create a generator for `backend`, seeded from the given state.
+    ///
+    /// The xoshiro backend uses the state as is, so rngs keep producing the
+    /// same values they did before backends were configurable.
+    pub(crate) fn new(backend: Backend, state: Xoshiro256StarStar) -> Self {
+        match backend {
+            Backend::Xoshiro256StarStar => Self::Xoshiro256StarStar(state),
+            Backend::Pcg64 => Self::Pcg64(Pcg64::from_rng(state).expect("failed to create rng")),
+        }
+    }
+
+    /// Create the generator [`Generator::new`] creates from
+    /// `Xoshiro256StarStar::seed_from_u64(seed)`, advanced by `steps` 64-bit
+    /// outputs.
+    ///
+    /// This takes the same time whatever the number of steps: xoshiro jumps
+    /// ahead along its characteristic polynomial, and PCG advances its LCG.
+    pub(crate) fn advanced(backend: Backend, seed: u64, steps: u64) -> Self {
+        let state = xoshiro_state(seed);
+
+        match backend {
+            Backend::Xoshiro256StarStar => {
+                Self::Xoshiro256StarStar(xoshiro_from_state(xoshiro_jump(state, steps)))
+            }
+            Backend::Pcg64 => {
+                let mut pcg =
+                    Pcg64::from_rng(xoshiro_from_state(state)).expect("failed to create rng");
+                pcg.advance(u128::from(steps));
+                Self::Pcg64(pcg)
+            }
+        }
+    }
+
+    pub(crate) fn backend(&self) -> Backend {
+        match self {
+            Self::Xoshiro256StarStar(_) => Backend::Xoshiro256StarStar,
+            Self::Pcg64(_) => Backend::Pcg64,
+        }
+    }
+
+    fn rng(&mut self) -> &mut dyn RngCore {
+        match self {
+            Self::Xoshiro256StarStar(rng) => rng,
+            Self::Pcg64(rng) => rng,
+        }
+    }
+}
+
+impl RngCore for Generator {
+    fn next_u32(&mut self) -> u32 {
+        self.rng().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng().try_fill_bytes(dest)
+    }
+}
+
+/// Returns the state `Xoshiro256StarStar::seed_from_u64` starts from, which
+/// fills the state with the first outputs of SplitMix64.
+fn xoshiro_state(seed: u64) -> [u64; 4] {
+    let mut splitmix = SplitMix64::seed_from_u64(seed);
+    let mut state = [0; 4];
+    for word in &mut state {
+        *word = splitmix.next_u64();
+    }
+
+    state
+}
+
+fn xoshiro_from_state(state: [u64; 4]) -> Xoshiro256StarStar {
+    let mut seed = [0; 32];
+    for (bytes, word) in seed.chunks_exact_mut(8).zip(&state) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+
+    Xoshiro256StarStar::from_seed(seed)
+}
+
+/// The xoshiro256 state transition, which every output advances by one.
+fn xoshiro_step(s: &mut [u64; 4]) {
+    let t = s[1] << 17;
+    s[2] ^= s[0];
+    s[3] ^= s[1];
+    s[1] ^= s[2];
+    s[0] ^= s[3];
+    s[2] ^= t;
+    s[3] = s[3].rotate_left(45);
+}
+
+/// Returns the state xoshiro256 reaches from `state` after `steps` outputs.
+///
+/// The transition is linear, so with `x^steps = sum(c_i x^i)` modulo its
+/// characteristic polynomial, the state after `steps` outputs is the sum of
+/// the states after `i` outputs with `c_i = 1`, for `i < 256`.
+fn xoshiro_jump(state: [u64; 4], steps: u64) -> [u64; 4] {
+    xoshiro_apply(state, x_pow(steps))
+}
+
+/// Returns the sum of the states after `i` outputs from `state`, for each
+/// coefficient `c_i = 1` of the polynomial.
+fn xoshiro_apply(mut state: [u64; 4], polynomial: [u64; 4]) -> [u64; 4] {
+    let mut sum = [0; 4];
+    for bit in 0..256 {
+        if polynomial[bit / 64] & 1 << (bit % 64) != 0 {
+            for (sum, word) in sum.iter_mut().zip(&state) {
+                *sum ^= word;
+            }
+        }
+
+        xoshiro_step(&mut state);
+    }
+
+    sum
+}
+
+/// Returns `x^exponent` modulo [`XOSHIRO_POLYNOMIAL`], by squaring.
+fn x_pow(mut exponent: u64) -> [u64; 4] {
+    let mut result = [1, 0, 0, 0];
+    let mut base = [2, 0, 0, 0];
+
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = mul_mod(result, base);
+        }
+
+        base = mul_mod(base, base);
+        exponent >>= 1;
+    }
+
+    result
+}
+
+/// Multiplies two polynomials over GF(2) modulo [`XOSHIRO_POLYNOMIAL`].
+fn mul_mod(a: [u64; 4], b: [u64; 4]) -> [u64; 4] {
+    let mut result = [0u64; 4];
+
+    for bit in (0..256).rev() {
+        // Multiply by `x`, reducing the `x^256` term.
+        let overflow = result[3] >> 63 != 0;
+        for word in (1..4).rev() {
+            result[word] = result[word] << 1 | result[word - 1] >> 63;
+        }
+        result[0] <<= 1;
+        if overflow {
+            for (result, word) in result.iter_mut().zip(&XOSHIRO_POLYNOMIAL) {
+                *result ^= word;
+            }
+        }
+
+        if a[bit / 64] & 1 << (bit % 64) != 0 {
+            for (result, word) in result.iter_mut().zip(&b) {
+                *result ^= word;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_matches_seed_from_u64() {
+        for seed in [0, 1, 42, u64::MAX].iter() {
+            assert_eq!(
+                xoshiro_from_state(xoshiro_state(*seed)),
+                Xoshiro256StarStar::seed_from_u64(*seed)
+            );
+        }
+    }
+
+    #[test]
+    fn polynomial_matches_jump() {
+        // `x^(2^128)`, by squaring `x` 128 times.
+        let mut polynomial = [2, 0, 0, 0];
+        for _ in 0..128 {
+            polynomial = mul_mod(polynomial, polynomial);
+        }
+
+        let mut jumped = Xoshiro256StarStar::seed_from_u64(7);
+        jumped.jump();
+
+        let state = xoshiro_apply(xoshiro_state(7), polynomial);
+        assert_eq!(xoshiro_from_state(state), jumped);
+    }
+
+    #[test]
+    fn advanced_matches_stepping() {
+        for backend in [Backend::Xoshiro256StarStar, Backend::Pcg64].iter() {
+            let mut stepped = Generator::new(*backend, Xoshiro256StarStar::seed_from_u64(3));
+
+            for steps in 0..300 {
+                assert_eq!(Generator::advanced(*backend, 3, steps), stepped);
+                stepped.next_u64();
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use crate::bevy::prelude::*;
+use crate::table::pick_weighted;
+use crate::{hash, CounterRng, Seed};
+use std::ops::Range;
+
+/// A layer of seeded value noise, e.g. the temperature of a world.
+///
+/// The noise ranges from `0.0` to `1.0`, and only depends on the seed, the
+/// label of the channel and the point it is sampled at, so any part of the
+/// world can be sampled in any order. Channels with different labels are
+/// independent of each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseChannel {
+    /// The size of a feature of the noise, in cells. Larger scales produce
+    /// smoother noise.
+    pub scale: f32,
+
+    /// The number of layers of finer detail added on top of each other, each
+    /// at half the scale and half the strength of the previous one.
+    pub octaves: u32,
+
+    key: u64,
+}
+
+impl NoiseChannel {
+    /// Create a channel with a single octave.
+    ///
+    /// The scale is clamped to at least one cell.
+    pub fn new(label: &str, scale: f32) -> Self {
+        Self {
+            scale,
+            octaves: 1,
+            key: hash::combine(
+                hash::hash_bytes(b"noise"),
+                hash::hash_bytes(label.as_bytes()),
+            ),
+        }
+    }
+
+    /// Layer the given number of octaves, see [`NoiseChannel::octaves`].
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    /// Returns the noise of the seed at the given point, in cells.
+    pub fn sample(&self, seed: &Seed, point: Vec2) -> f32 {
+        self.sample_key(seed.key(), point)
+    }
+
+    fn sample_key(&self, seed: u64, point: Vec2) -> f32 {
+        let key = hash::combine(seed, self.key);
+
+        let mut scale = self.scale.max(1.0);
+        let mut strength = 1.0;
+        let mut total = 0.0;
+        let mut noise = 0.0;
+        for octave in 0..self.octaves.max(1) {
+            noise += strength * value_noise(hash::combine(key, u64::from(octave)), point / scale);
+            total += strength;
+            scale = (scale / 2.0).max(1.0);
+            strength /= 2.0;
+        }
+
+        noise / total
+    }
+}
+
+/// Bilinearly interpolated noise, with a random value at every lattice
+/// point.
+fn value_noise(key: u64, point: Vec2) -> f32 {
+    let cell = point.floor();
+    let (x, y) = (cell.x as i64, cell.y as i64);
+
+    let lattice = |dx: i64, dy: i64| {
+        let hash = hash::combine(hash::combine(key, (x + dx) as u64), (y + dy) as u64);
+        (hash >> 40) as f32 / (1u64 << 24) as f32
+    };
+
+    // Smoothstep, so the noise has no creases along the lattice.
+    let t = point - cell;
+    let t = t * t * (Vec2::splat(3.0) - t * 2.0);
+
+    let bottom = lattice(0, 0) + (lattice(1, 0) - lattice(0, 0)) * t.x;
+    let top = lattice(0, 1) + (lattice(1, 1) - lattice(0, 1)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+/// The noise values of a cell of a [`BiomeMap`], each from `0.0` to `1.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Climate {
+    /// How hot the cell is.
+    pub temperature: f32,
+
+    /// How wet the cell is.
+    pub moisture: f32,
+
+    /// How high the cell is, or `0.0` without an elevation channel.
+    pub elevation: f32,
+}
+
+/// An entry of the lookup table of a [`BiomeMap`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiomeRule<B> {
+    /// The biome of the cells matching the rule.
+    pub biome: B,
+
+    /// The temperatures the rule matches.
+    pub temperature: Range<f32>,
+
+    /// The moistures the rule matches.
+    pub moisture: Range<f32>,
+
+    /// The elevations the rule matches.
+    pub elevation: Range<f32>,
+
+    /// How likely this biome is picked, relative to the other rules matching
+    /// the same cell.
+    pub weight: f32,
+}
+
+impl<B> BiomeRule<B> {
+    /// Create a rule matching the temperatures and moistures at any
+    /// elevation, with a weight of `1.0`.
+    pub fn new(biome: B, temperature: Range<f32>, moisture: Range<f32>) -> Self {
+        Self {
+            biome,
+            temperature,
+            moisture,
+            elevation: 0.0..1.0,
+            weight: 1.0,
+        }
+    }
+
+    /// Only match the given elevations.
+    pub fn with_elevation(mut self, elevation: Range<f32>) -> Self {
+        self.elevation = elevation;
+        self
+    }
+
+    /// Set the weight of the rule.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Returns whether the climate falls into the ranges of the rule.
+    pub fn matches(&self, climate: &Climate) -> bool {
+        self.temperature.contains(&climate.temperature)
+            && self.moisture.contains(&climate.moisture)
+            && self.elevation.contains(&climate.elevation)
+    }
+}
+
+/// Classifies the cells of a grid into biomes, from seeded noise.
+///
+/// Every cell has a [`Climate`], sampled from a temperature, a moisture and
+/// optionally an elevation [`NoiseChannel`]. The biome of the cell is picked
+/// from the rules matching its climate, by weight, so overlapping rules blend
+/// biomes into each other:
+///
+/// ```rust,ignore
+/// let biomes = BiomeMap::new(&seed)
+///     .with_elevation(NoiseChannel::new("elevation", 96.0).with_octaves(4))
+///     .with_rule(BiomeRule::new(Biome::Ocean, 0.0..1.0, 0.0..1.0).with_elevation(0.0..0.3))
+///     .with_rule(BiomeRule::new(Biome::Desert, 0.6..1.0, 0.0..0.3))
+///     .with_rule(BiomeRule::new(Biome::Forest, 0.2..0.8, 0.4..1.0).with_weight(3.0))
+///     .with_rule(BiomeRule::new(Biome::Meadow, 0.2..0.8, 0.3..0.8));
+///
+/// let biome = biomes.biome(IVec2::new(12, -40));
+/// ```
+///
+/// The biome of a cell only depends on the seed, the channels, the rules and
+/// the position of the cell, so chunks can be generated in any order, and
+/// regenerated on every client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiomeMap<B> {
+    /// The temperature of the cells, `"temperature"` with a scale of 64 by
+    /// default.
+    pub temperature: NoiseChannel,
+
+    /// The moisture of the cells, `"moisture"` with a scale of 48 by
+    /// default.
+    pub moisture: NoiseChannel,
+
+    /// The elevation of the cells, if any.
+    pub elevation: Option<NoiseChannel>,
+
+    /// The lookup table, see [`BiomeMap::biome`].
+    pub rules: Vec<BiomeRule<B>>,
+
+    seed: u64,
+}
+
+impl<B> BiomeMap<B> {
+    /// Create a map of the given seed, without any rules.
+    pub fn new(seed: &Seed) -> Self {
+        Self {
+            temperature: NoiseChannel::new("temperature", 64.0).with_octaves(3),
+            moisture: NoiseChannel::new("moisture", 48.0).with_octaves(3),
+            elevation: None,
+            rules: vec![],
+            seed: seed.key(),
+        }
+    }
+
+    /// Sample temperatures from the given channel.
+    pub fn with_temperature(mut self, temperature: NoiseChannel) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sample moistures from the given channel.
+    pub fn with_moisture(mut self, moisture: NoiseChannel) -> Self {
+        self.moisture = moisture;
+        self
+    }
+
+    /// Sample elevations from the given channel.
+    pub fn with_elevation(mut self, elevation: NoiseChannel) -> Self {
+        self.elevation = Some(elevation);
+        self
+    }
+
+    /// Add a rule to the lookup table.
+    pub fn with_rule(mut self, rule: BiomeRule<B>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the climate of the cell.
+    pub fn climate(&self, cell: IVec2) -> Climate {
+        let point = Vec2::new(cell.x as f32, cell.y as f32);
+
+        Climate {
+            temperature: self.temperature.sample_key(self.seed, point),
+            moisture: self.moisture.sample_key(self.seed, point),
+            elevation: self
+                .elevation
+                .map_or(0.0, |elevation| elevation.sample_key(self.seed, point)),
+        }
+    }
+
+    /// Returns the biome of the cell, or `None` if no rule with a positive
+    /// weight matches its climate.
+    pub fn biome(&self, cell: IVec2) -> Option<&B> {
+        let climate = self.climate(cell);
+
+        let key = hash::combine(hash::hash_bytes(b"biome"), hash::cell_key(cell));
+        let mut rng = CounterRng::new(self.seed, key, 0);
+
+        let weights = self.rules.iter().map(|rule| {
+            if rule.matches(&climate) {
+                rule.weight
+            } else {
+                0.0
+            }
+        });
+        let index = pick_weighted(&mut rng, weights)?;
+
+        Some(&self.rules[index].biome)
+    }
+
+    /// Returns the biomes of the `size` cells starting at `min`, row by row.
+    pub fn generate(&self, min: IVec2, size: UVec2) -> Vec<Option<&B>> {
+        (0..size.y as i32)
+            .flat_map(|y| (0..size.x as i32).map(move |x| min + IVec2::new(x, y)))
+            .map(|cell| self.biome(cell))
+            .collect()
+    }
+}
@@ -0,0 +1,521 @@
+use crate::backend::Generator;
+use crate::bevy::prelude::*;
+use crate::replay::{
+    read_seed, read_str, read_u16, read_u32, read_u64, write_len, write_seed, write_str,
+    ReplayError,
+};
+use crate::{
+    derive_seed, FrameRng, Rng, RngStreams, RngSystem, RootRng, Seed, StableIdAllocator, TickRng,
+};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The bytes every checkpoint file starts with.
+pub const CHECKPOINT_MAGIC: [u8; 4] = *b"BRCP";
+
+/// The checkpoint format version written by [`save_checkpoint`].
+pub const CHECKPOINT_VERSION: u16 = 1;
+
+/// How far an [`Rng`] has advanced along its stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RngPosition {
+    /// The number of values drawn, see [`Rng::seed_info`].
+    pub draws: u64,
+
+    /// The number of 64-bit outputs the generator produced for those draws.
+    pub steps: u64,
+}
+
+impl Rng {
+    /// Returns how far this rng has advanced along its stream.
+    pub fn position(&self) -> RngPosition {
+        let rng = self.synced();
+
+        RngPosition {
+            draws: rng.draws,
+            steps: rng.steps,
+        }
+    }
+
+    /// Move a derived rng to the given position of its stream, returning
+    /// `false` for rngs that can't be derived again, like forks.
+    fn seek(&mut self, position: RngPosition) -> bool {
+        self.sync();
+
+        let key = match self.key {
+            Some(key) => key,
+            None => return false,
+        };
+
+        // The generator `derive` creates, jumped ahead, so resuming doesn't
+        // take longer the longer the session ran.
+        let seed = derive_seed(self.seed.key(), key);
+        self.inner = Generator::advanced(self.backend(), seed, position.steps);
+        self.draws = position.draws;
+        self.steps = position.steps;
+        true
+    }
+}
+
+/// The state of the rngs managed by the [`RngPlugin`](crate::RngPlugin), to
+/// resume a session where it left off.
+///
+/// This covers the seed, the [`FrameRng`], the [`TickRng`], the declared
+/// [`RngStreams`] and the [`StableIdAllocator`]. Every derived rng is stored
+/// by its position only, and derived again and jumped ahead to it when the
+/// checkpoint is restored, so checkpoints stay small and restore quickly no
+/// matter how long the session ran.
+///
+/// `Local<Rng>` instances, forks and rngs held in your own components aren't
+/// covered; draw long-running simulations from the declared streams, or from
+/// the `TickRng`, to resume them deterministically.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RngCheckpoint {
+    /// The seed of the session.
+    pub seed: Seed,
+
+    /// The current frame of the [`FrameRng`].
+    pub frame: u64,
+
+    /// The current tick of the [`TickRng`].
+    pub tick: u64,
+
+    /// The position of the [`TickRng`] within the current tick.
+    pub tick_rng: RngPosition,
+
+    /// The position of each declared stream, by label.
+    pub streams: BTreeMap<String, RngPosition>,
+
+    /// The number of ids handed out by the [`StableIdAllocator`].
+    pub stable_ids: u64,
+}
+
+impl RngCheckpoint {
+    /// Capture the rng state of the world, or `None` if the
+    /// [`RngPlugin`](crate::RngPlugin) wasn't added.
+    pub fn capture(world: &World) -> Option<Self> {
+        Some(Self::new(
+            world.get_resource()?,
+            world.get_resource()?,
+            world.get_resource()?,
+            world.get_resource()?,
+            world.get_resource()?,
+        ))
+    }
+
+    fn new(
+        seed: &Seed,
+        frame_rng: &FrameRng,
+        tick_rng: &TickRng,
+        streams: &RngStreams,
+        ids: &StableIdAllocator,
+    ) -> Self {
+        Self {
+            seed: seed.clone(),
+            frame: frame_rng.frame(),
+            tick: tick_rng.tick(),
+            tick_rng: tick_rng.position(),
+            streams: streams
+                .labels()
+                .filter_map(|label| Some((label.to_owned(), streams.get(label)?.position())))
+                .collect(),
+            stable_ids: ids.allocated(),
+        }
+    }
+
+    /// Restore the captured rng state into the world.
+    ///
+    /// The plugin switches to the captured seed, and every rng continues
+    /// with the value it would have drawn next when the checkpoint was
+    /// captured. Streams that are no longer declared are skipped.
+    pub fn restore(&self, world: &mut World) {
+        if let Some(root) = world.get_resource::<RootRng>() {
+            if root.shared.root.read().unwrap().seed != self.seed {
+                root.reseed(self.seed.clone());
+            }
+        }
+
+        world.insert_resource(self.seed.clone());
+        world.insert_resource(StableIdAllocator::with_allocated(self.stable_ids));
+
+        if let Some(mut frame_rng) = world.get_resource_mut::<FrameRng>() {
+            frame_rng.reseed(&self.seed);
+            frame_rng.set_frame(self.frame);
+        }
+
+        if let Some(mut tick_rng) = world.get_resource_mut::<TickRng>() {
+            tick_rng.reseed(&self.seed);
+            tick_rng.set_tick(self.tick);
+            tick_rng.seek(self.tick_rng);
+        }
+
+        if let Some(mut streams) = world.get_resource_mut::<RngStreams>() {
+            for (label, position) in &self.streams {
+                match streams.get_mut(label) {
+                    Some(rng) => {
+                        rng.seek(*position);
+                    }
+                    None => warn!("checkpoint stream {} is not declared, skipping", label),
+                }
+            }
+        }
+    }
+}
+
+/// The error returned by [`load_checkpoint`].
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// Reading the checkpoint failed.
+    Io(io::Error),
+
+    /// The data doesn't start with [`CHECKPOINT_MAGIC`], it isn't a
+    /// checkpoint.
+    InvalidMagic,
+
+    /// The checkpoint was written with an unsupported format version.
+    UnsupportedVersion(u16),
+
+    /// The checkpoint data is malformed.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read checkpoint: {}", err),
+            Self::InvalidMagic => f.write_str("not a checkpoint file"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported checkpoint version {} (expected at most {})",
+                version, CHECKPOINT_VERSION
+            ),
+            Self::Corrupt(reason) => write!(f, "corrupt checkpoint: {}", reason),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::from(err).into()
+    }
+}
+
+impl From<ReplayError> for CheckpointError {
+    fn from(err: ReplayError) -> Self {
+        match err {
+            ReplayError::Io(err) => Self::Io(err),
+            ReplayError::InvalidMagic => Self::InvalidMagic,
+            ReplayError::UnsupportedVersion(version) => Self::UnsupportedVersion(version),
+            ReplayError::Corrupt(reason) => Self::Corrupt(reason),
+        }
+    }
+}
+
+/// Write a checkpoint in the versioned checkpoint format.
+///
+/// The format is: [`CHECKPOINT_MAGIC`], [`CHECKPOINT_VERSION`], the seed, the
+/// frame and tick, the position of the `TickRng`, the number of stable ids,
+/// and the position of each stream. All integers are little-endian.
+pub fn save_checkpoint<W: Write>(mut writer: W, checkpoint: &RngCheckpoint) -> io::Result<()> {
+    writer.write_all(&CHECKPOINT_MAGIC)?;
+    writer.write_all(&CHECKPOINT_VERSION.to_le_bytes())?;
+
+    write_seed(&mut writer, &checkpoint.seed)?;
+    writer.write_all(&checkpoint.frame.to_le_bytes())?;
+    writer.write_all(&checkpoint.tick.to_le_bytes())?;
+    write_position(&mut writer, checkpoint.tick_rng)?;
+    writer.write_all(&checkpoint.stable_ids.to_le_bytes())?;
+
+    write_len(&mut writer, checkpoint.streams.len())?;
+    for (label, position) in &checkpoint.streams {
+        write_str(&mut writer, label)?;
+        write_position(&mut writer, *position)?;
+    }
+
+    writer.flush()
+}
+
+/// Read a checkpoint written by [`save_checkpoint`].
+///
+/// Data that isn't a checkpoint, or was written in a newer format version,
+/// is rejected with an error instead of being misinterpreted.
+pub fn load_checkpoint<R: Read>(mut reader: R) -> Result<RngCheckpoint, CheckpointError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != CHECKPOINT_MAGIC {
+        return Err(CheckpointError::InvalidMagic);
+    }
+
+    let version = read_u16(&mut reader)?;
+    if version == 0 || version > CHECKPOINT_VERSION {
+        return Err(CheckpointError::UnsupportedVersion(version));
+    }
+
+    let seed = read_seed(&mut reader, true)?;
+    let frame = read_u64(&mut reader)?;
+    let tick = read_u64(&mut reader)?;
+    let tick_rng = read_position(&mut reader)?;
+    let stable_ids = read_u64(&mut reader)?;
+
+    let mut streams = BTreeMap::new();
+    for _ in 0..read_u32(&mut reader)? {
+        let label = read_str(&mut reader)?;
+        streams.insert(label, read_position(&mut reader)?);
+    }
+
+    Ok(RngCheckpoint {
+        seed,
+        frame,
+        tick,
+        tick_rng,
+        streams,
+        stable_ids,
+    })
+}
+
+fn write_position<W: Write>(writer: &mut W, position: RngPosition) -> io::Result<()> {
+    writer.write_all(&position.draws.to_le_bytes())?;
+    writer.write_all(&position.steps.to_le_bytes())
+}
+
+fn read_position<R: Read>(reader: &mut R) -> io::Result<RngPosition> {
+    Ok(RngPosition {
+        draws: read_u64(reader)?,
+        steps: read_u64(reader)?,
+    })
+}
+
+/// How often the [`CheckpointPlugin`] saves a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckpointInterval {
+    /// Every given number of seconds.
+    Seconds(f32),
+
+    /// Every given number of ticks of the [`TickRng`].
+    Ticks(u64),
+}
+
+/// Saves an [`RngCheckpoint`] to a file periodically, and resumes from it
+/// when the app starts.
+///
+/// ```rust,ignore
+/// App::build()
+///     .add_plugin(RngPlugin::builder().seed(42).streams(&["terrain"]).build())
+///     .add_plugin(CheckpointPlugin::new("rng.checkpoint").every_ticks(600))
+/// ```
+///
+/// If the file exists when the plugin is added, the checkpoint is restored
+/// right away, so systems start drawing where the previous process left off.
+/// Checkpoints are saved at the end of the frame, and written to a temporary
+/// file first, so a crash while saving never leaves a damaged checkpoint
+/// behind. Errors are logged rather than interrupting the app.
+///
+/// Add it after the [`RngPlugin`](crate::RngPlugin), with the same streams
+/// declared, so there is state to restore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointPlugin {
+    path: PathBuf,
+    interval: CheckpointInterval,
+}
+
+impl CheckpointPlugin {
+    /// Create a plugin saving to the given file every 10 seconds.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: CheckpointInterval::Seconds(10.0),
+        }
+    }
+
+    /// Save a checkpoint every given number of seconds.
+    pub fn every_seconds(mut self, seconds: f32) -> Self {
+        self.interval = CheckpointInterval::Seconds(seconds);
+        self
+    }
+
+    /// Save a checkpoint every given number of ticks of the [`TickRng`].
+    pub fn every_ticks(mut self, ticks: u64) -> Self {
+        self.interval = CheckpointInterval::Ticks(ticks);
+        self
+    }
+}
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if self.path.exists() {
+            let world = app.world_mut();
+            match read_checkpoint(&self.path) {
+                Ok(checkpoint) => {
+                    info!("resuming rng state from {}", self.path.display());
+                    checkpoint.restore(world);
+                }
+                Err(err) => error!("failed to resume from {}: {}", self.path.display(), err),
+            }
+        }
+
+        app.insert_resource(CheckpointSettings {
+            path: self.path.clone(),
+            interval: self.interval,
+            elapsed: 0.0,
+            last_tick: None,
+        })
+        .add_system_to_stage(
+            CoreStage::Last,
+            save_checkpoints.system().after(RngSystem::Derive),
+        );
+    }
+}
+
+struct CheckpointSettings {
+    path: PathBuf,
+    interval: CheckpointInterval,
+    elapsed: f32,
+    last_tick: Option<u64>,
+}
+
+fn read_checkpoint(path: &Path) -> Result<RngCheckpoint, CheckpointError> {
+    load_checkpoint(BufReader::new(File::open(path)?))
+}
+
+/// Write the checkpoint next to `path`, then move it into place.
+fn write_checkpoint(path: &Path, checkpoint: &RngCheckpoint) -> io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    save_checkpoint(BufWriter::new(File::create(&temp)?), checkpoint)?;
+    fs::rename(temp, path)
+}
+
+fn save_checkpoints(
+    time: Res<Time>,
+    mut settings: ResMut<CheckpointSettings>,
+    seed: Res<Seed>,
+    frame_rng: Res<FrameRng>,
+    tick_rng: Res<TickRng>,
+    streams: Res<RngStreams>,
+    ids: Res<StableIdAllocator>,
+) {
+    let due = match settings.interval {
+        CheckpointInterval::Seconds(seconds) => {
+            settings.elapsed += time.delta_seconds();
+            settings.elapsed >= seconds
+        }
+        CheckpointInterval::Ticks(ticks) => {
+            let last = *settings.last_tick.get_or_insert(tick_rng.tick());
+            tick_rng.tick().wrapping_sub(last) >= ticks
+        }
+    };
+
+    if !due {
+        return;
+    }
+
+    settings.elapsed = 0.0;
+    settings.last_tick = Some(tick_rng.tick());
+
+    let checkpoint = RngCheckpoint::new(&seed, &frame_rng, &tick_rng, &streams, &ids);
+    if let Err(err) = write_checkpoint(&settings.path, &checkpoint) {
+        error!(
+            "failed to save checkpoint to {}: {}",
+            settings.path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Backend;
+    use rand::RngCore;
+
+    #[test]
+    fn save_load_seek() {
+        for backend in [Backend::Xoshiro256StarStar, Backend::Pcg64].iter() {
+            let seed = Seed::from("checkpoint");
+            let mut rng = seed.stream_rng_with(*backend, "loot");
+            for _ in 0..1000 {
+                rng.next_u64();
+            }
+            rng.next_u32();
+            rng.fill_bytes(&mut [0; 13]);
+
+            let checkpoint = RngCheckpoint {
+                seed: seed.clone(),
+                frame: 3,
+                tick: 7,
+                tick_rng: RngPosition::default(),
+                streams: vec![("loot".to_owned(), rng.position())]
+                    .into_iter()
+                    .collect(),
+                stable_ids: 11,
+            };
+
+            let mut bytes = vec![];
+            save_checkpoint(&mut bytes, &checkpoint).unwrap();
+            let loaded = load_checkpoint(&bytes[..]).unwrap();
+            assert_eq!(loaded, checkpoint);
+
+            let mut resumed = seed.stream_rng_with(*backend, "loot");
+            assert!(resumed.seek(loaded.streams["loot"]));
+            assert_eq!(resumed.position(), rng.position());
+            assert_eq!(resumed.next_u64(), rng.next_u64());
+        }
+    }
+
+    #[test]
+    fn forks_cannot_seek() {
+        let mut fork = Seed::from(1).stream_rng("loot").fork();
+        assert!(!fork.seek(RngPosition::default()));
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert!(matches!(
+            load_checkpoint(&b"BRRPxxxx"[..]),
+            Err(CheckpointError::InvalidMagic)
+        ));
+
+        let mut bytes = CHECKPOINT_MAGIC.to_vec();
+        bytes.extend_from_slice(&(CHECKPOINT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            load_checkpoint(&bytes[..]),
+            Err(CheckpointError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn writes_next_to_the_target() {
+        let dir = std::env::temp_dir().join(format!("bevy_rng_checkpoint_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let checkpoint = RngCheckpoint {
+            seed: Seed::from(1),
+            frame: 0,
+            tick: 0,
+            tick_rng: RngPosition::default(),
+            streams: BTreeMap::new(),
+            stable_ids: 0,
+        };
+
+        let path = dir.join("rng.tmp");
+        write_checkpoint(&path, &checkpoint).unwrap();
+        assert_eq!(read_checkpoint(&path).unwrap(), checkpoint);
+        assert!(!dir.join("rng.tmp.tmp").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use crate::bevy::prelude::*;
+use crate::{hash, Backend, Rng, RootRng, Seed};
+use std::collections::{BTreeMap, HashMap};
+
+/// The number of chunk rngs a [`ChunkRngs`] resource keeps by default.
+pub const DEFAULT_CHUNK_CAPACITY: usize = 1024;
+
+impl Seed {
+    /// Returns the rng of the chunk at the given coordinates.
+    ///
+    /// This is the rng [`ChunkRngs::get`] starts with for that chunk, which
+    /// lets you compute it outside of a system.
+    ///
+    /// The rng uses the default [`Backend`], see [`Rng::backend`].
+    pub fn chunk_rng(&self, chunk: IVec2) -> Rng {
+        Rng::derived(Backend::default(), self.clone(), None, chunk_key(chunk))
+    }
+}
+
+/// The key the rng of the chunk at the given coordinates is derived from.
+fn chunk_key(chunk: IVec2) -> u64 {
+    hash::combine(hash::hash_bytes(b"chunk"), hash::cell_key(chunk))
+}
+
+impl RootRng {
+    /// Returns the rng of the chunk at the given coordinates, following seed
+    /// swaps.
+    fn chunk_rng(&self, chunk: IVec2) -> Rng {
+        let root = self.shared.root.read().unwrap().clone();
+
+        Rng {
+            link: Some(self.link(&root)),
+            ..root.derived(None, chunk_key(chunk))
+        }
+    }
+}
+
+/// The `ChunkRngs` resource holds an rng for every chunk of an infinite
+/// world.
+///
+/// The rng of a chunk is derived from the seed and the coordinates of the
+/// chunk when it is first requested, so chunks can be generated in any
+/// order, and every client generates the same content for the same chunk:
+///
+/// ```rust,ignore
+/// app.add_plugin(RngPlugin::from(42)).init_resource::<ChunkRngs>();
+///
+/// fn load_chunks(mut chunks: ResMut<ChunkRngs>, mut requests: EventReader<LoadChunk>) {
+///     for request in requests.iter() {
+///         let rng = chunks.get(request.chunk);
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Only the most recently used rngs are kept, up to the capacity of the
+/// resource, [`DEFAULT_CHUNK_CAPACITY`] by default. An evicted chunk starts
+/// over from its first value when requested again, so draw everything a
+/// chunk needs while it is loaded, or raise the capacity above the number of
+/// chunks loaded at once.
+///
+/// The rngs follow seed swaps through [`SeedSync`](crate::SeedSync).
+#[derive(Debug, Clone)]
+pub struct ChunkRngs {
+    root: RootRng,
+    capacity: usize,
+    chunks: HashMap<IVec2, Entry>,
+    recent: BTreeMap<u64, IVec2>,
+    uses: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    rng: Rng,
+    used: u64,
+}
+
+impl ChunkRngs {
+    /// The maximum number of chunk rngs kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Set the maximum number of chunk rngs kept, evicting the least
+    /// recently used ones above it. The capacity is at least one.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict();
+    }
+
+    /// The number of chunk rngs kept.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns `true` if no chunk rngs are kept.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns `true` if the rng of the chunk is kept.
+    pub fn contains(&self, chunk: IVec2) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Returns the rng of the chunk, deriving it if it isn't kept.
+    pub fn get(&mut self, chunk: IVec2) -> &mut Rng {
+        self.uses += 1;
+        let used = self.uses;
+
+        match self.chunks.get_mut(&chunk) {
+            Some(entry) => {
+                self.recent.remove(&entry.used);
+                entry.used = used;
+            }
+            None => {
+                let rng = self.root.chunk_rng(chunk);
+                self.chunks.insert(chunk, Entry { rng, used });
+            }
+        }
+
+        self.recent.insert(used, chunk);
+        self.evict();
+
+        &mut self.chunks.get_mut(&chunk).expect("just used").rng
+    }
+
+    /// Forget the rng of the chunk, e.g. when it is unloaded, returning it if
+    /// it was kept.
+    pub fn remove(&mut self, chunk: IVec2) -> Option<Rng> {
+        let entry = self.chunks.remove(&chunk)?;
+        self.recent.remove(&entry.used);
+        Some(entry.rng)
+    }
+
+    /// Forget the rngs of all chunks.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.recent.clear();
+    }
+
+    fn evict(&mut self) {
+        while self.chunks.len() > self.capacity {
+            let (&used, &chunk) = self.recent.iter().next().expect("every chunk is recent");
+            self.recent.remove(&used);
+            self.chunks.remove(&chunk);
+        }
+    }
+}
+
+impl FromWorld for ChunkRngs {
+    fn from_world(world: &mut World) -> Self {
+        let root = match world.get_resource::<RootRng>() {
+            Some(root) => root.clone(),
+            None => RootRng::new(Seed::from_entropy(), Backend::default()),
+        };
+
+        Self {
+            root,
+            capacity: DEFAULT_CHUNK_CAPACITY,
+            chunks: HashMap::new(),
+            recent: BTreeMap::new(),
+            uses: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    fn chunks(capacity: usize) -> ChunkRngs {
+        ChunkRngs {
+            root: RootRng::new(Seed::from(42), Backend::default()),
+            capacity,
+            chunks: HashMap::new(),
+            recent: BTreeMap::new(),
+            uses: 0,
+        }
+    }
+
+    #[test]
+    fn starts_like_the_seed() {
+        let mut chunks = chunks(DEFAULT_CHUNK_CAPACITY);
+        for chunk in [IVec2::ZERO, IVec2::new(-3, 7), IVec2::new(7, -3)].iter() {
+            let mut expected = Seed::from(42).chunk_rng(*chunk);
+            assert_eq!(chunks.get(*chunk).next_u64(), expected.next_u64());
+            assert_eq!(chunks.get(*chunk).next_u64(), expected.next_u64());
+        }
+
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let mut chunks = chunks(2);
+        let (a, b, c) = (IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(2, 0));
+
+        chunks.get(a);
+        chunks.get(b);
+        chunks.get(a);
+        chunks.get(c);
+        assert!(chunks.contains(a) && !chunks.contains(b) && chunks.contains(c));
+
+        chunks.get(b);
+        assert!(!chunks.contains(a) && chunks.contains(b) && chunks.contains(c));
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.recent.len(), 2);
+    }
+
+    #[test]
+    fn evicted_chunks_start_over() {
+        let mut chunks = chunks(1);
+        let first = chunks.get(IVec2::ZERO).next_u64();
+        chunks.get(IVec2::new(1, 1));
+        assert_eq!(chunks.get(IVec2::ZERO).next_u64(), first);
+
+        chunks.set_capacity(2);
+        chunks.get(IVec2::new(1, 1));
+        assert_ne!(chunks.get(IVec2::ZERO).next_u64(), first);
+    }
+
+    #[test]
+    fn set_capacity_evicts() {
+        let mut chunks = chunks(8);
+        for x in 0..8 {
+            chunks.get(IVec2::new(x, 0));
+        }
+
+        chunks.set_capacity(3);
+        assert_eq!(chunks.len(), 3);
+        assert!((5..8).all(|x| chunks.contains(IVec2::new(x, 0))));
+
+        chunks.set_capacity(0);
+        assert_eq!(chunks.capacity(), 1);
+        assert!(chunks.contains(IVec2::new(7, 0)));
+
+        assert!(chunks.remove(IVec2::new(7, 0)).is_some());
+        assert!(chunks.is_empty() && chunks.recent.is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::replay::write_seed;
+use crate::Seed;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+
+/// Prefixes every hashed message, so commitments can't be mistaken for
+/// other SHA-256 hashes.
+const COMMITMENT_DOMAIN: &[u8] = b"bevy_rng commitment v1";
+const COMBINE_DOMAIN: &[u8] = b"bevy_rng combined seed v1";
+
+/// A seed the host committed to, but hasn't revealed yet.
+///
+/// Commit-reveal lets the players of a competitive game check that the host
+/// didn't pick or change a seed to its advantage:
+///
+/// 1. The host creates a `CommittedSeed` and sends its [`Commitment`] to
+///    every client, keeping the seed itself secret.
+/// 2. Each client sends a seed of its own, which the host can't predict.
+/// 3. The host sends the [`Reveal`], every client checks it against the
+///    commitment with [`Reveal::verify`], and everyone plays with
+///    [`Reveal::combine`] of the host seed and the client seeds.
+///
+/// ```rust,ignore
+/// // Host
+/// let committed = CommittedSeed::new(Seed::from(host_choice));
+/// send(Message::Commit(committed.commitment()));
+/// // ... receive the client seeds ...
+/// send(Message::Reveal(committed.reveal()));
+///
+/// // Client
+/// let host_seed = reveal.verify(&commitment)?;
+/// let seed = reveal.combine(&client_seeds);
+/// ```
+///
+/// The commitment is a SHA-256 hash of the seed and a random salt: it doesn't
+/// give the seed away, and the host can't find another seed matching it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommittedSeed {
+    seed: Seed,
+    salt: [u8; 32],
+}
+
+impl CommittedSeed {
+    /// Commit to the seed, with a salt drawn from the operating system's
+    /// entropy source.
+    pub fn new(seed: Seed) -> Self {
+        let mut salt = [0; 32];
+        OsRng.fill_bytes(&mut salt);
+
+        Self::with_salt(seed, salt)
+    }
+
+    /// Commit to the seed with the given salt.
+    ///
+    /// The salt keeps clients from guessing the seed by trying likely ones,
+    /// it must be kept secret until the reveal, and never be reused.
+    pub fn with_salt(seed: Seed, salt: [u8; 32]) -> Self {
+        Self { seed, salt }
+    }
+
+    /// The seed committed to.
+    pub fn seed(&self) -> &Seed {
+        &self.seed
+    }
+
+    /// Returns the commitment to send to the other players.
+    pub fn commitment(&self) -> Commitment {
+        Commitment {
+            hash: commitment_hash(&self.seed, &self.salt),
+        }
+    }
+
+    /// Returns the message revealing the seed.
+    pub fn reveal(&self) -> Reveal {
+        Reveal {
+            seed: self.seed.clone(),
+            salt: self.salt,
+        }
+    }
+}
+
+/// The hash a [`CommittedSeed`] is bound to, see [`Reveal::verify`].
+///
+/// The `Display` implementation renders the hash as lowercase hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Commitment {
+    /// The SHA-256 hash of the committed seed and its salt.
+    pub hash: [u8; 32],
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.hash
+            .iter()
+            .try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+/// The message revealing a committed seed, see [`CommittedSeed`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reveal {
+    /// The committed seed.
+    pub seed: Seed,
+
+    /// The salt the seed was committed with.
+    pub salt: [u8; 32],
+}
+
+impl Reveal {
+    /// Check that this is the seed the commitment was made to, returning
+    /// the seed if it is.
+    pub fn verify(&self, commitment: &Commitment) -> Result<&Seed, RevealError> {
+        if commitment_hash(&self.seed, &self.salt) == commitment.hash {
+            Ok(&self.seed)
+        } else {
+            Err(RevealError)
+        }
+    }
+
+    /// Combine the revealed seed with the seeds contributed by the other
+    /// players, in the order given.
+    ///
+    /// No player can steer the result without knowing every other seed up
+    /// front, which the commitment rules out for the host.
+    pub fn combine(&self, contributions: &[Seed]) -> Seed {
+        let mut hasher = Sha256::new();
+        hasher.update(COMBINE_DOMAIN);
+        update_seed(&mut hasher, &self.seed);
+        for seed in contributions {
+            update_seed(&mut hasher, seed);
+        }
+
+        let hash: [u8; 32] = hasher.finalize().into();
+        Seed::Bytes(hash.to_vec())
+    }
+}
+
+/// The error returned by [`Reveal::verify`] when the revealed seed doesn't
+/// match the commitment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RevealError;
+
+impl fmt::Display for RevealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("revealed seed doesn't match its commitment")
+    }
+}
+
+impl Error for RevealError {}
+
+fn commitment_hash(seed: &Seed, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(salt);
+    update_seed(&mut hasher, seed);
+
+    hasher.finalize().into()
+}
+
+/// Hash the seed in the encoding of replay files, which tells every seed
+/// apart, e.g. `Seed::Number(1)` from `Seed::String("1")`.
+fn update_seed(hasher: &mut Sha256, seed: &Seed) {
+    let mut bytes = vec![];
+    write_seed(&mut bytes, seed).expect("writing to a vec can't fail");
+    hasher.update(&bytes);
+}
@@ -0,0 +1,141 @@
+use crate::bevy::prelude::*;
+use crate::{hash, Backend, Rng, RootRng, Seed, StableId, StableIdAllocator};
+use std::ops::{Deref, DerefMut};
+
+/// The `RngComponent` component gives an entity an rng of its own.
+///
+/// Add the [`NeedsRng`] marker when spawning an entity, and the
+/// [`RngComponentPlugin`] replaces it with an `RngComponent` seeded from the
+/// seed and the [`StableId`] of the entity:
+///
+/// ```rust,ignore
+/// commands.spawn_bundle((Goblin, StableId::from_name("goblin 7"), NeedsRng));
+///
+/// fn wander(mut goblins: Query<&mut RngComponent, With<Goblin>>) {
+///     for mut rng in goblins.iter_mut() {
+///         let turn: f32 = rng.gen_range(-1.0..1.0);
+///     }
+/// }
+/// ```
+///
+/// Each entity draws from its own stream, so the values it gets don't depend
+/// on the order entities are processed in, or on the other entities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngComponent {
+    rng: Rng,
+}
+
+impl RngComponent {
+    /// Wrap the given rng.
+    pub fn new(rng: Rng) -> Self {
+        Self { rng }
+    }
+
+    /// Returns the underlying rng.
+    pub fn into_inner(self) -> Rng {
+        self.rng
+    }
+}
+
+impl Deref for RngComponent {
+    type Target = Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for RngComponent {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+/// Marks an entity to receive an [`RngComponent`], see
+/// [`RngComponentPlugin`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NeedsRng;
+
+impl Seed {
+    /// Returns the rng of the entity with the given id.
+    ///
+    /// This is the rng the [`RngComponentPlugin`] attaches to the entity,
+    /// which lets you compute it outside of a system.
+    ///
+    /// The rng uses the default [`Backend`], see [`Rng::backend`].
+    pub fn entity_rng(&self, id: StableId) -> Rng {
+        self.entity_rng_with(Backend::default(), id)
+    }
+
+    pub(crate) fn entity_rng_with(&self, backend: Backend, id: StableId) -> Rng {
+        Rng::derived(backend, self.clone(), None, entity_key(id))
+    }
+}
+
+/// The key the rng of the entity with the given id is derived from.
+fn entity_key(id: StableId) -> u64 {
+    hash::combine(hash::hash_bytes(b"entity"), id.key())
+}
+
+impl RootRng {
+    /// Returns the rng of the entity with the given id, following seed swaps.
+    fn entity_rng(&self, id: StableId) -> Rng {
+        let root = self.shared.root.read().unwrap().clone();
+
+        Rng {
+            link: Some(self.link(&root)),
+            ..root.derived(None, entity_key(id))
+        }
+    }
+}
+
+/// Replaces the [`NeedsRng`] marker of every entity with an
+/// [`RngComponent`], at the start of each frame.
+///
+/// The rng is derived from the seed and the [`StableId`] of the entity, so
+/// it is the same on every client and in every run. Entities without a
+/// `StableId` are given one from the [`StableIdAllocator`] first, by
+/// ascending `Entity` index. Bevy reuses the indices of despawned entities,
+/// so this is only the order they were spawned in until an entity is
+/// despawned, and it is only stable if entities are spawned and despawned in
+/// the same order every time.
+///
+/// Add it after the [`RngPlugin`](crate::RngPlugin), so the rngs are seeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RngComponentPlugin;
+
+impl Plugin for RngComponentPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<StableIdAllocator>()
+            .add_system_to_stage(CoreStage::PreUpdate, attach_rngs.system());
+    }
+}
+
+fn attach_rngs(
+    mut commands: Commands,
+    root: Option<Res<RootRng>>,
+    mut ids: ResMut<StableIdAllocator>,
+    query: Query<(Entity, Option<&StableId>), With<NeedsRng>>,
+) {
+    let mut entities: Vec<_> = query.iter().collect();
+    entities.sort_by_key(|(entity, _)| (entity.id(), entity.generation()));
+
+    for (entity, id) in entities {
+        let mut commands = commands.entity(entity);
+        let id = match id {
+            Some(id) => *id,
+            None => {
+                let id = ids.allocate();
+                commands.insert(id);
+                id
+            }
+        };
+
+        let rng = match &root {
+            Some(root) => root.entity_rng(id),
+            None => Seed::from_entropy().entity_rng(id),
+        };
+
+        commands.remove::<NeedsRng>().insert(RngComponent::new(rng));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::bevy::asset::{AssetEvent, AssetLoader, AssetServer, LoadContext, LoadedAsset};
+use crate::bevy::prelude::*;
+use crate::bevy::reflect::TypeUuid;
+use crate::bevy::utils::{BoxedFuture, Uuid};
+use crate::table::pick_weighted;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The file extension of [`RandomConfig`] assets.
+///
+/// The files contain RON, but use their own extension, so the loader doesn't
+/// claim the `.ron` files of other loaders.
+pub const RANDOM_CONFIG_EXTENSION: &str = "rng";
+
+/// How the value of a [`RandomConfig`] entry is drawn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RandomValue {
+    /// Always the given value.
+    Constant(f64),
+
+    /// A float in `[min, max)`, uniformly distributed.
+    Uniform(f64, f64),
+
+    /// An integer in `[min, max]`, uniformly distributed.
+    Integer(i64, i64),
+
+    /// A float in `[min, max)`, clustering around the middle more tightly the
+    /// more samples are averaged, see [`Curve::Centered`](crate::Curve).
+    Centered(f64, f64, u32),
+
+    /// Succeeds with the given probability.
+    Chance(f64),
+
+    /// One of the names, picked with a probability proportional to its
+    /// weight.
+    Table(Vec<(String, f32)>),
+}
+
+impl RandomValue {
+    /// Draw a number, or `None` for chances and tables.
+    ///
+    /// Exactly one value is drawn, except for `Centered`, which draws one
+    /// per sample. Empty ranges produce their start.
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<f64> {
+        let value = match *self {
+            Self::Constant(value) => value,
+            Self::Uniform(min, max) => min + (max - min).max(0.0) * rng.gen::<f64>(),
+            Self::Integer(min, max) if min < max => rng.gen_range(min..=max) as f64,
+            Self::Integer(min, _) => min as f64,
+            Self::Centered(min, max, samples) => {
+                let samples = samples.max(1);
+                let t = (0..samples).map(|_| rng.gen::<f64>()).sum::<f64>() / f64::from(samples);
+                min + (max - min).max(0.0) * t
+            }
+            Self::Chance(_) | Self::Table(_) => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Roll a chance, or `None` for other values. Exactly one value is
+    /// drawn.
+    pub fn check<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<bool> {
+        match *self {
+            Self::Chance(chance) => Some(rng.gen::<f64>() < chance),
+            _ => None,
+        }
+    }
+
+    /// Pick a name from a table, or `None` for other values, or if no entry
+    /// has a positive weight.
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&str> {
+        match self {
+            Self::Table(entries) => {
+                let index = pick_weighted(rng, entries.iter().map(|(_, weight)| *weight))?;
+                Some(&entries[index].0)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The `RandomConfig` resource holds named random values, tuned in a RON
+/// file rather than in code.
+///
+/// ```ron
+/// {
+///     "goblin.health": Uniform(20.0, 35.0),
+///     "goblin.gold": Integer(0, 12),
+///     "goblin.crit": Chance(0.05),
+///     "goblin.loot": Table([("dagger", 5.0), ("potion", 2.0), ("nothing", 20.0)]),
+/// }
+/// ```
+///
+/// Values are drawn from the rng you pass in, so the config only decides
+/// _how_ values are distributed, while the seeded streams keep deciding
+/// _which_ values come out:
+///
+/// ```rust,ignore
+/// fn spawn_goblin(config: Res<RandomConfig>, mut rng: Local<StreamRng<Goblins>>) {
+///     let health = config.roll("goblin.health", &mut *rng).unwrap_or(25.0);
+///     let loot = config.pick("goblin.loot", &mut *rng);
+/// }
+/// ```
+///
+/// Load it from RON with [`RandomConfig::from_ron`], or add the
+/// [`RandomConfigPlugin`] to load it as an asset, and reload the resource
+/// whenever the file changes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RandomConfig {
+    entries: BTreeMap<String, RandomValue>,
+}
+
+impl TypeUuid for RandomConfig {
+    const TYPE_UUID: Uuid = Uuid::from_bytes([
+        0x6b, 0x1e, 0x52, 0x0c, 0x3f, 0x8a, 0x4d, 0x27, 0x9e, 0x41, 0xd3, 0x05, 0x7a, 0xc2, 0x18,
+        0x96,
+    ]);
+}
+
+impl RandomConfig {
+    /// Create an empty config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a config from RON, a map from names to [`RandomValue`]s.
+    pub fn from_ron(ron: &str) -> Result<Self, ron::Error> {
+        ron::de::from_str(ron)
+    }
+
+    /// Set the value with the given name.
+    pub fn insert(&mut self, name: impl Into<String>, value: RandomValue) {
+        self.entries.insert(name.into(), value);
+    }
+
+    /// Returns the value with the given name.
+    pub fn get(&self, name: &str) -> Option<&RandomValue> {
+        self.entries.get(name)
+    }
+
+    /// Returns the names of all values, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Draw the number with the given name, see [`RandomValue::roll`].
+    ///
+    /// Returns `None` without drawing if there is no such number.
+    pub fn roll<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Option<f64> {
+        self.get(name)?.roll(rng)
+    }
+
+    /// Roll the chance with the given name, see [`RandomValue::check`].
+    ///
+    /// Returns `None` without drawing if there is no such chance.
+    pub fn check<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Option<bool> {
+        self.get(name)?.check(rng)
+    }
+
+    /// Pick from the table with the given name, see [`RandomValue::pick`].
+    ///
+    /// Returns `None` without drawing if there is no such table.
+    pub fn pick<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> Option<&str> {
+        self.get(name)?.pick(rng)
+    }
+}
+
+/// Loads [`RandomConfig`] assets from `.rng` files, see
+/// [`RANDOM_CONFIG_EXTENSION`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RandomConfigLoader;
+
+impl AssetLoader for RandomConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let config: RandomConfig = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[RANDOM_CONFIG_EXTENSION]
+    }
+}
+
+/// Loads a [`RandomConfig`] asset into the `RandomConfig` resource, and
+/// reloads the resource whenever the asset changes.
+///
+/// ```rust,ignore
+/// app.add_plugins(DefaultPlugins)
+///     .add_plugin(RandomConfigPlugin::new("config/goblins.rng"));
+/// ```
+///
+/// Enable hot reloading on the `AssetServer` to pick up changes while the
+/// game runs. The resource is empty until the asset is loaded.
+///
+/// Add it after Bevy's `AssetPlugin`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RandomConfigPlugin {
+    path: PathBuf,
+}
+
+impl RandomConfigPlugin {
+    /// Load the config from the given asset path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Plugin for RandomConfigPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<RandomConfig>()
+            .init_asset_loader::<RandomConfigLoader>();
+
+        let handle = app
+            .world_mut()
+            .get_resource::<AssetServer>()
+            .expect("add the RandomConfigPlugin after the AssetPlugin")
+            .load(self.path.as_path());
+
+        app.insert_resource(RandomConfigHandle(handle))
+            .init_resource::<RandomConfig>()
+            .add_system_to_stage(CoreStage::PreUpdate, reload_config.system());
+    }
+}
+
+struct RandomConfigHandle(Handle<RandomConfig>);
+
+fn reload_config(
+    handle: Res<RandomConfigHandle>,
+    assets: Res<Assets<RandomConfig>>,
+    mut events: EventReader<AssetEvent<RandomConfig>>,
+    mut config: ResMut<RandomConfig>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(&handle.0) {
+                    *config = loaded.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::bevy::prelude::*;
+use crate::Rng;
+use std::time::Duration;
+
+/// How much a duration varies, see [`RngVaryExt::vary`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variation {
+    /// Up to the given percentage of the duration, in both directions.
+    Percent(u32),
+
+    /// Up to the given number of seconds, in both directions.
+    Seconds(f32),
+}
+
+impl Variation {
+    /// The largest offset from `duration`, in seconds.
+    fn spread(&self, duration: Duration) -> f64 {
+        match *self {
+            Self::Percent(percent) => duration.as_secs_f64() * f64::from(percent) / 100.0,
+            Self::Seconds(seconds) => f64::from(seconds.abs()),
+        }
+    }
+}
+
+/// Add jitter to durations.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+pub trait RngVaryExt: rand::Rng {
+    /// Returns a duration uniformly distributed around `duration`, within
+    /// the given variation.
+    ///
+    /// The result is never negative. Exactly one value is drawn, even without
+    /// any variation, so tuning the variation doesn't shift later draws.
+    ///
+    /// ```rust,ignore
+    /// let delay = rng.vary(Duration::from_secs(2), Variation::Percent(15));
+    /// ```
+    fn vary(&mut self, duration: Duration, variation: Variation) -> Duration {
+        let spread = variation.spread(duration);
+        let offset = (self.gen::<f64>() * 2.0 - 1.0) * spread;
+
+        Duration::from_secs_f64((duration.as_secs_f64() + offset).max(0.0))
+    }
+}
+
+impl<R: rand::Rng + ?Sized> RngVaryExt for R {}
+
+/// The `VariedCooldown` component is a cooldown whose duration is rerolled
+/// every time it is reset, e.g. for the attack pattern of an enemy.
+///
+/// The cooldown is ticked by the [`RngPlugin`](crate::RngPlugin) with the
+/// frame time. Act once it [`is_ready`](VariedCooldown::is_ready), then
+/// [`reset`](VariedCooldown::reset) it:
+///
+/// ```rust,ignore
+/// fn attack(mut enemies: Query<&mut VariedCooldown, With<Enemy>>) {
+///     for mut cooldown in enemies.iter_mut() {
+///         if cooldown.is_ready() {
+///             // Attack...
+///             cooldown.reset();
+///         }
+///     }
+/// }
+/// ```
+///
+/// Each cooldown draws from its own rng, typically forked from a system's rng
+/// with [`Rng::fork`], so the durations stay reproducible under the seed,
+/// regardless of the order in which entities are processed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariedCooldown {
+    /// The duration the cooldown varies around.
+    pub base: Duration,
+
+    /// How much the duration varies on each reset.
+    pub variation: Variation,
+
+    rng: Rng,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl VariedCooldown {
+    /// Create a cooldown drawing from the given rng, starting with a freshly
+    /// rolled duration.
+    pub fn new(mut rng: Rng, base: Duration, variation: Variation) -> Self {
+        let duration = rng.vary(base, variation);
+
+        Self {
+            base,
+            variation,
+            rng,
+            duration,
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// Returns `true` once the cooldown has passed.
+    pub fn is_ready(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// The duration rolled for the current cooldown.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The time left until the cooldown is ready.
+    pub fn remaining(&self) -> Duration {
+        self.duration.checked_sub(self.elapsed).unwrap_or_default()
+    }
+
+    /// Advance the cooldown by `delta`.
+    pub fn tick(&mut self, delta: Duration) {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+    }
+
+    /// Start the cooldown over, with a new duration.
+    pub fn reset(&mut self) {
+        self.duration = self.rng.vary(self.base, self.variation);
+        self.elapsed = Duration::from_secs(0);
+    }
+}
+
+pub(crate) fn tick_cooldowns(time: Res<Time>, mut cooldowns: Query<&mut VariedCooldown>) {
+    for mut cooldown in cooldowns.iter_mut() {
+        cooldown.tick(time.delta());
+    }
+}
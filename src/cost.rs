@@ -0,0 +1,48 @@
+use crate::bevy::prelude::*;
+use crate::{hash, Rng, RngFloatExt, StableId};
+
+impl Rng {
+    /// Returns a factor between `1 - magnitude` and `1 + magnitude` to
+    /// multiply a pathfinding cost with.
+    ///
+    /// Jittering costs makes agents take slightly different routes through
+    /// the same terrain, instead of all following the one optimal path. The
+    /// factor is computed from the seed and `key` only, see [`Rng::at`],
+    /// so it doesn't advance this rng, doesn't depend on the order the
+    /// pathfinder visits tiles in, and is the same every time a tile is
+    /// visited. Key the noise per agent, with [`tile_cost_key`] or
+    /// [`edge_cost_key`]:
+    ///
+    /// ```rust,ignore
+    /// let cost = |from: IVec2, to: IVec2| {
+    ///     terrain.cost(to) * rng.cost_noise(edge_cost_key(agent, from, to), 0.15)
+    /// };
+    /// ```
+    ///
+    /// The magnitude is clamped to `[0, 1]`, so the factor is never negative.
+    pub fn cost_noise(&self, key: u64, magnitude: f32) -> f32 {
+        1.0 + magnitude.clamp(0.0, 1.0) * self.at(key, 0).snorm()
+    }
+}
+
+/// The key of the [`Rng::cost_noise`] of entering `tile`, for `agent`.
+pub fn tile_cost_key(agent: StableId, tile: IVec2) -> u64 {
+    hash::combine(
+        hash::combine(hash::hash_bytes(b"tile cost"), agent.key()),
+        hash::cell_key(tile),
+    )
+}
+
+/// The key of the [`Rng::cost_noise`] of moving from `from` to `to`, for
+/// `agent`.
+///
+/// Edges are directed: moving back from `to` to `from` has a different key.
+pub fn edge_cost_key(agent: StableId, from: IVec2, to: IVec2) -> u64 {
+    hash::combine(
+        hash::combine(
+            hash::combine(hash::hash_bytes(b"edge cost"), agent.key()),
+            hash::cell_key(from),
+        ),
+        hash::cell_key(to),
+    )
+}
@@ -0,0 +1,108 @@
+use crate::hash;
+use rand::RngCore;
+
+const M0: u32 = 0xd251_1f53;
+const M1: u32 = 0xcd9e_8d57;
+const W0: u32 = 0x9e37_79b9;
+const W1: u32 = 0xbb67_ae85;
+const ROUNDS: usize = 10;
+
+/// A stateless, counter-based random number generator (Philox 4x32-10).
+///
+/// Every value is a pure function of `(seed, key, index)`: there is no state
+/// carried between calls, so the value for an index never depends on which
+/// other values were (or were not) generated before it.
+///
+/// This is what makes randomness independent of entity iteration order: use
+/// a stable per-entity number as the `key`, and the entity will always get the
+/// same values, regardless of where it shows up in a query.
+///
+/// A `CounterRng` implements `rand::RngCore`, so you can pull any amount of
+/// data out of a single `(key, index)` pair, without overlapping other pairs.
+///
+/// Obtain one through [`Rng::at`](crate::Rng::at), or create one directly
+/// with [`CounterRng::new`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CounterRng {
+    key: [u32; 2],
+    counter: [u32; 4],
+    block: [u32; 4],
+    cursor: usize,
+}
+
+impl CounterRng {
+    /// Create a generator positioned at `index` of the `key` stream of the
+    /// given seed key.
+    pub fn new(seed: u64, key: u64, index: u64) -> Self {
+        let key = hash::combine(seed, key);
+
+        Self {
+            key: [key as u32, (key >> 32) as u32],
+            counter: [0, 0, index as u32, (index >> 32) as u32],
+            block: [0; 4],
+            cursor: 4,
+        }
+    }
+
+    fn refill(&mut self) {
+        self.block = philox(self.counter, self.key);
+        self.cursor = 0;
+
+        // The lower half of the counter numbers the blocks within a single
+        // `(key, index)` pair, the upper half holds the index itself.
+        let block = (u64::from(self.counter[1]) << 32 | u64::from(self.counter[0])).wrapping_add(1);
+        self.counter[0] = block as u32;
+        self.counter[1] = (block >> 32) as u32;
+    }
+}
+
+impl RngCore for CounterRng {
+    fn next_u32(&mut self) -> u32 {
+        if self.cursor == self.block.len() {
+            self.refill();
+        }
+
+        let value = self.block[self.cursor];
+        self.cursor += 1;
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = u64::from(self.next_u32());
+        let high = u64::from(self.next_u32());
+        high << 32 | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn philox(mut counter: [u32; 4], mut key: [u32; 2]) -> [u32; 4] {
+    for round in 0..ROUNDS {
+        if round > 0 {
+            key[0] = key[0].wrapping_add(W0);
+            key[1] = key[1].wrapping_add(W1);
+        }
+
+        let p0 = u64::from(M0) * u64::from(counter[0]);
+        let p1 = u64::from(M1) * u64::from(counter[2]);
+
+        counter = [
+            (p1 >> 32) as u32 ^ counter[1] ^ key[0],
+            p1 as u32,
+            (p0 >> 32) as u32 ^ counter[3] ^ key[1],
+            p0 as u32,
+        ];
+    }
+
+    counter
+}
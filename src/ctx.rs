@@ -0,0 +1,170 @@
+use crate::bevy::ecs::archetype::Archetype;
+use crate::bevy::ecs::system::{
+    LocalState, ResMutState, SystemParam, SystemParamFetch, SystemParamState, SystemState,
+};
+use crate::bevy::prelude::*;
+use crate::{Rng, RngStreams, RootRng, Seed};
+use std::ops::{Deref, DerefMut};
+
+/// The label of the stream the [`GlobalRng`] draws from.
+pub const GLOBAL_STREAM: &str = "global";
+
+/// The `GlobalRng` resource holds one rng shared by every system accessing
+/// it, e.g. through [`RngCtx::global`].
+///
+/// It produces the same values as the stream labelled [`GLOBAL_STREAM`], and
+/// follows seed swaps through [`SeedSync`](crate::SeedSync). Systems sharing
+/// it draw in the order they run, so only draw from it in systems ordered
+/// against each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalRng {
+    rng: Rng,
+}
+
+impl GlobalRng {
+    /// Returns the underlying rng.
+    pub fn into_inner(self) -> Rng {
+        self.rng
+    }
+}
+
+impl FromWorld for GlobalRng {
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world.get_resource::<RootRng>() {
+            Some(root) => root.stream_rng(GLOBAL_STREAM),
+            None => Seed::from_entropy().stream_rng(GLOBAL_STREAM),
+        };
+
+        Self { rng }
+    }
+}
+
+impl Deref for GlobalRng {
+    type Target = Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for GlobalRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+/// A system parameter bundling every rng a system can draw from.
+///
+/// ```rust,ignore
+/// fn drop_loot(mut ctx: RngCtx) {
+///     let crit = ctx.local().gen_bool(0.1);
+///     let item = ctx.stream("loot").gen_range(0..10);
+///     let weather = ctx.global().gen::<f32>();
+/// }
+/// ```
+///
+/// - [`RngCtx::local`] is the `Local<Rng>` of the system.
+/// - [`RngCtx::global`] is the [`GlobalRng`] shared by all systems.
+/// - [`RngCtx::stream`] is one of the [`RngStreams`] declared with
+///   [`RngPluginBuilder::streams`](crate::RngPluginBuilder::streams).
+///
+/// All of them follow seed swaps through [`SeedSync`](crate::SeedSync) the
+/// same way, so none of them keeps drawing from the old seed after a swap.
+///
+/// The global rng and the streams are borrowed mutably, so systems taking an
+/// `RngCtx` never run in parallel with each other, or with systems accessing
+/// those resources. Take a `Local<Rng>` instead where that matters.
+pub struct RngCtx<'a> {
+    local: Local<'a, Rng>,
+    global: ResMut<'a, GlobalRng>,
+    streams: ResMut<'a, RngStreams>,
+}
+
+impl<'a> RngCtx<'a> {
+    /// Returns the rng of this system.
+    pub fn local(&mut self) -> &mut Rng {
+        &mut self.local
+    }
+
+    /// Returns the rng shared by all systems.
+    pub fn global(&mut self) -> &mut Rng {
+        &mut self.global
+    }
+
+    /// Returns the rng of the stream with the given label.
+    ///
+    /// # Panics
+    ///
+    /// If no stream with that label was declared.
+    pub fn stream(&mut self, label: &str) -> &mut Rng {
+        match self.streams.get_mut(label) {
+            Some(rng) => rng,
+            None => panic!(
+                "rng stream {:?} wasn't declared, add it with `RngPlugin::builder().streams(..)`",
+                label
+            ),
+        }
+    }
+
+    /// Returns the rng of the stream with the given label, if it was declared.
+    pub fn try_stream(&mut self, label: &str) -> Option<&mut Rng> {
+        self.streams.get_mut(label)
+    }
+}
+
+// `#[derive(SystemParam)]` finds the bevy crate by package name, which would
+// pick the wrong one with both bevy features available, so the parameter is
+// implemented by hand, delegating to the state of each field.
+impl<'a> SystemParam for RngCtx<'a> {
+    type Fetch = RngCtxState;
+}
+
+/// The state of an [`RngCtx`] parameter.
+pub struct RngCtxState {
+    local: LocalState<Rng>,
+    global: ResMutState<GlobalRng>,
+    streams: ResMutState<RngStreams>,
+}
+
+unsafe impl SystemParamState for RngCtxState {
+    type Config = ();
+
+    fn init(world: &mut World, system_state: &mut SystemState, _config: Self::Config) -> Self {
+        Self {
+            local: LocalState::init(world, system_state, None),
+            global: ResMutState::init(world, system_state, ()),
+            streams: ResMutState::init(world, system_state, ()),
+        }
+    }
+
+    fn new_archetype(&mut self, archetype: &Archetype, system_state: &mut SystemState) {
+        self.local.new_archetype(archetype, system_state);
+        self.global.new_archetype(archetype, system_state);
+        self.streams.new_archetype(archetype, system_state);
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        self.local.apply(world);
+        self.global.apply(world);
+        self.streams.apply(world);
+    }
+
+    fn default_config() -> Self::Config {}
+}
+
+impl<'a> SystemParamFetch<'a> for RngCtxState {
+    type Item = RngCtx<'a>;
+
+    unsafe fn get_param(
+        state: &'a mut Self,
+        system_state: &'a SystemState,
+        world: &'a World,
+        change_tick: u32,
+    ) -> Self::Item {
+        RngCtx {
+            local: LocalState::get_param(&mut state.local, system_state, world, change_tick),
+            global: ResMutState::get_param(&mut state.global, system_state, world, change_tick),
+            streams: ResMutState::get_param(&mut state.streams, system_state, world, change_tick),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::bevy::prelude::*;
+use crate::Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+/// The checksum of a single stream at a given tick.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickChecksum {
+    /// The tick the checksum was taken at.
+    pub tick: u64,
+
+    /// The stream the checksum belongs to, `None` for unnamed rngs.
+    pub stream: Option<String>,
+
+    /// The checksum of the rng, see [`Rng::checksum`].
+    pub checksum: u64,
+}
+
+/// The first point at which two instances disagreed about an rng's state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Divergence {
+    /// The first tick at which the checksums differed.
+    pub tick: u64,
+
+    /// The stream whose checksums differed.
+    pub stream: Option<String>,
+
+    /// The checksum recorded locally.
+    pub local: u64,
+
+    /// The checksum received from the other side.
+    pub remote: u64,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rng diverged at tick {}", self.tick)?;
+
+        if let Some(stream) = &self.stream {
+            write!(f, " on stream {}", stream)?;
+        }
+
+        write!(f, " ({:016x} != {:016x})", self.local, self.remote)
+    }
+}
+
+type Key = (u64, Option<String>);
+
+/// The `DivergenceDetector` resource compares per-tick rng checksums with
+/// another app instance, and reports the first tick and stream at which they
+/// differ.
+///
+/// Checksums are exchanged over channels: bridge them to your network layer to
+/// compare two processes, use [`DivergenceDetector::pair`] for two apps in the
+/// same process, or [`DivergenceDetector::from_trace`] to compare a run
+/// against a recorded trace.
+///
+/// Record the checksum of every rng you care about at the end of each tick
+/// with [`DivergenceDetector::record`]. Add the [`DivergencePlugin`] to get a
+/// [`Divergence`] event as soon as a mismatch is found.
+#[derive(Debug)]
+pub struct DivergenceDetector {
+    outgoing: Option<Mutex<Sender<TickChecksum>>>,
+    incoming: Option<Mutex<Receiver<TickChecksum>>>,
+    local: HashMap<Key, u64>,
+    remote: HashMap<Key, u64>,
+    divergence: Option<Divergence>,
+    updated: bool,
+}
+
+impl DivergenceDetector {
+    /// Create a detector sending its checksums to `outgoing`, and comparing
+    /// them to the ones received from `incoming`.
+    pub fn new(outgoing: Sender<TickChecksum>, incoming: Receiver<TickChecksum>) -> Self {
+        Self {
+            outgoing: Some(Mutex::new(outgoing)),
+            incoming: Some(Mutex::new(incoming)),
+            ..Self::from_trace(vec![])
+        }
+    }
+
+    /// Create two detectors connected to each other.
+    pub fn pair() -> (Self, Self) {
+        let (left_tx, left_rx) = mpsc::channel();
+        let (right_tx, right_rx) = mpsc::channel();
+
+        (Self::new(left_tx, right_rx), Self::new(right_tx, left_rx))
+    }
+
+    /// Create a detector comparing the local checksums against a recorded
+    /// trace.
+    pub fn from_trace(trace: impl IntoIterator<Item = TickChecksum>) -> Self {
+        let remote = trace
+            .into_iter()
+            .map(|checksum| ((checksum.tick, checksum.stream), checksum.checksum))
+            .collect();
+
+        Self {
+            outgoing: None,
+            incoming: None,
+            local: HashMap::new(),
+            remote,
+            divergence: None,
+            updated: false,
+        }
+    }
+
+    /// Record the checksum of `rng` at the given tick, and send it to the
+    /// other side.
+    pub fn record(&mut self, tick: u64, rng: &Rng) {
+        self.record_checksum(TickChecksum {
+            tick,
+            stream: rng.seed_info().stream,
+            checksum: rng.checksum(),
+        });
+    }
+
+    /// Record a checksum, and send it to the other side.
+    ///
+    /// A disconnected channel is not an error: the checksum is still compared
+    /// against anything received before the disconnect.
+    pub fn record_checksum(&mut self, checksum: TickChecksum) {
+        if let Some(outgoing) = &self.outgoing {
+            let _ = outgoing.lock().unwrap().send(checksum.clone());
+        }
+
+        let key = (checksum.tick, checksum.stream);
+        match self.remote.remove(&key) {
+            Some(remote) => self.compare(key, checksum.checksum, remote),
+            None => {
+                self.local.insert(key, checksum.checksum);
+            }
+        }
+    }
+
+    /// Process all checksums received from the other side.
+    ///
+    /// Returns the first divergence, if one was found that wasn't returned by
+    /// an earlier call.
+    pub fn poll(&mut self) -> Option<Divergence> {
+        let received: Vec<_> = match &self.incoming {
+            Some(incoming) => incoming.lock().unwrap().try_iter().collect(),
+            None => vec![],
+        };
+
+        for checksum in received {
+            let key = (checksum.tick, checksum.stream);
+            match self.local.remove(&key) {
+                Some(local) => self.compare(key, local, checksum.checksum),
+                None => {
+                    self.remote.insert(key, checksum.checksum);
+                }
+            }
+        }
+
+        if !self.updated {
+            return None;
+        }
+
+        self.updated = false;
+        self.divergence.clone()
+    }
+
+    /// The first divergence found so far.
+    pub fn divergence(&self) -> Option<&Divergence> {
+        self.divergence.as_ref()
+    }
+
+    fn compare(&mut self, (tick, stream): Key, local: u64, remote: u64) {
+        if local == remote {
+            return;
+        }
+
+        if matches!(&self.divergence, Some(divergence) if divergence.tick <= tick) {
+            return;
+        }
+
+        self.divergence = Some(Divergence {
+            tick,
+            stream,
+            local,
+            remote,
+        });
+        self.updated = true;
+    }
+}
+
+/// `DivergencePlugin` polls the [`DivergenceDetector`] resource (if present)
+/// every frame, and emits a [`Divergence`] event whenever an earlier
+/// divergence is found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DivergencePlugin;
+
+impl Plugin for DivergencePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<Divergence>()
+            .add_system_to_stage(CoreStage::Last, detect_divergence.system());
+    }
+}
+
+fn detect_divergence(
+    detector: Option<ResMut<DivergenceDetector>>,
+    mut divergences: EventWriter<Divergence>,
+) {
+    if let Some(divergence) = detector.and_then(|mut detector| detector.poll()) {
+        divergences.send(divergence);
+    }
+}
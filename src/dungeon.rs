@@ -0,0 +1,339 @@
+use crate::bevy::prelude::*;
+use rand::Rng;
+
+/// The parameters of a dungeon generated with [`DungeonConfig::generate`].
+///
+/// All sizes are in grid cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DungeonConfig {
+    /// The size of the area the dungeon is generated in.
+    pub size: IVec2,
+
+    /// The minimum size of a room on each axis.
+    pub min_room_size: i32,
+
+    /// The number of cells kept free between a room and the edge of its
+    /// partition, so rooms never touch.
+    pub padding: i32,
+
+    /// How many times the area is split in two, at most. A depth of `n`
+    /// produces up to `2^n` rooms.
+    pub max_depth: u32,
+
+    /// The number of corridors added between random rooms on top of the ones
+    /// connecting every room, to create loops.
+    pub extra_corridors: u32,
+}
+
+impl Default for DungeonConfig {
+    fn default() -> Self {
+        Self {
+            size: IVec2::new(64, 64),
+            min_room_size: 4,
+            padding: 1,
+            max_depth: 4,
+            extra_corridors: 2,
+        }
+    }
+}
+
+/// A rectangular room of a [`Dungeon`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Room {
+    /// The cell with the lowest coordinates inside the room.
+    pub min: IVec2,
+
+    /// The size of the room.
+    pub size: IVec2,
+}
+
+impl Room {
+    /// The first cell past the room, on both axes.
+    pub fn max(&self) -> IVec2 {
+        self.min + self.size
+    }
+
+    /// The cell at the center of the room (rounded down).
+    pub fn center(&self) -> IVec2 {
+        self.min + self.size / 2
+    }
+
+    /// Whether the cell is inside the room.
+    pub fn contains(&self, cell: IVec2) -> bool {
+        let max = self.max();
+        cell.x >= self.min.x && cell.y >= self.min.y && cell.x < max.x && cell.y < max.y
+    }
+}
+
+/// An L-shaped corridor connecting two rooms of a [`Dungeon`].
+///
+/// It runs in a straight line from the center of `from` to `corner`, and
+/// from there to the center of `to`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Corridor {
+    /// The index of the first room.
+    pub from: usize,
+
+    /// The index of the second room.
+    pub to: usize,
+
+    /// The cell where the corridor turns.
+    pub corner: IVec2,
+}
+
+/// A room-and-corridor layout, generated with [`DungeonConfig::generate`].
+///
+/// Rooms are the nodes and corridors the edges of a connected graph: every
+/// room can be reached from every other room. Rendering and meshing are left
+/// to you.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dungeon {
+    /// The rooms of the dungeon.
+    pub rooms: Vec<Room>,
+
+    /// The corridors between rooms.
+    pub corridors: Vec<Corridor>,
+}
+
+impl Dungeon {
+    /// Returns the indices of the rooms connected to the given room.
+    pub fn neighbours(&self, room: usize) -> impl Iterator<Item = usize> + '_ {
+        self.corridors
+            .iter()
+            .filter_map(move |corridor| match corridor {
+                corridor if corridor.from == room => Some(corridor.to),
+                corridor if corridor.to == room => Some(corridor.from),
+                _ => None,
+            })
+    }
+}
+
+impl DungeonConfig {
+    /// Generate a dungeon by binary space partitioning.
+    ///
+    /// The area is recursively split in two at a random position, a room of
+    /// random size is placed in each final partition, and the two halves of
+    /// each split are connected with a corridor. The layout only depends on
+    /// the config and the state of `rng`.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Dungeon {
+        let mut dungeon = Dungeon::default();
+        let area = Room {
+            min: IVec2::ZERO,
+            size: self.size,
+        };
+
+        self.split(rng, &mut dungeon, area, 0);
+
+        if dungeon.rooms.len() > 1 {
+            for _ in 0..self.extra_corridors {
+                let from = rng.gen_range(0..dungeon.rooms.len() as u32) as usize;
+                let to = rng.gen_range(0..dungeon.rooms.len() as u32) as usize;
+                if from != to {
+                    connect(rng, &mut dungeon, from, to);
+                }
+            }
+        }
+
+        dungeon
+    }
+
+    /// Partition `area`, and return the range of rooms placed inside it.
+    fn split<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        dungeon: &mut Dungeon,
+        area: Room,
+        depth: u32,
+    ) -> std::ops::Range<usize> {
+        let min_partition = self.min_room_size + 2 * self.padding;
+        let can_split_x = area.size.x >= 2 * min_partition;
+        let can_split_y = area.size.y >= 2 * min_partition;
+
+        if depth >= self.max_depth || !(can_split_x || can_split_y) {
+            return self.place_room(rng, dungeon, area);
+        }
+
+        // Prefer cutting across the longer side, to avoid long thin partitions.
+        let split_x = match (can_split_x, can_split_y) {
+            (true, false) => true,
+            (false, true) => false,
+            _ if area.size.x * 4 > area.size.y * 5 => true,
+            _ if area.size.y * 4 > area.size.x * 5 => false,
+            _ => rng.gen(),
+        };
+
+        let (first, second) = if split_x {
+            let at = rng.gen_range(min_partition..=area.size.x - min_partition);
+            (
+                Room {
+                    min: area.min,
+                    size: IVec2::new(at, area.size.y),
+                },
+                Room {
+                    min: area.min + IVec2::new(at, 0),
+                    size: IVec2::new(area.size.x - at, area.size.y),
+                },
+            )
+        } else {
+            let at = rng.gen_range(min_partition..=area.size.y - min_partition);
+            (
+                Room {
+                    min: area.min,
+                    size: IVec2::new(area.size.x, at),
+                },
+                Room {
+                    min: area.min + IVec2::new(0, at),
+                    size: IVec2::new(area.size.x, area.size.y - at),
+                },
+            )
+        };
+
+        let first = self.split(rng, dungeon, first, depth + 1);
+        let second = self.split(rng, dungeon, second, depth + 1);
+
+        if !first.is_empty() && !second.is_empty() {
+            let from = rng.gen_range(first.start as u32..first.end as u32) as usize;
+            let to = rng.gen_range(second.start as u32..second.end as u32) as usize;
+            connect(rng, dungeon, from, to);
+        }
+
+        first.start.min(second.start)..first.end.max(second.end)
+    }
+
+    fn place_room<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        dungeon: &mut Dungeon,
+        area: Room,
+    ) -> std::ops::Range<usize> {
+        let start = dungeon.rooms.len();
+        let space = area.size - IVec2::splat(2 * self.padding);
+        if space.x < self.min_room_size || space.y < self.min_room_size {
+            return start..start;
+        }
+
+        let size = IVec2::new(
+            rng.gen_range(self.min_room_size..=space.x),
+            rng.gen_range(self.min_room_size..=space.y),
+        );
+        let offset = IVec2::new(
+            rng.gen_range(0..=space.x - size.x),
+            rng.gen_range(0..=space.y - size.y),
+        );
+
+        dungeon.rooms.push(Room {
+            min: area.min + IVec2::splat(self.padding) + offset,
+            size,
+        });
+
+        start..start + 1
+    }
+}
+
+fn connect<R: Rng + ?Sized>(rng: &mut R, dungeon: &mut Dungeon, from: usize, to: usize) {
+    let (a, b) = (dungeon.rooms[from].center(), dungeon.rooms[to].center());
+    let corner = if rng.gen() {
+        IVec2::new(b.x, a.y)
+    } else {
+        IVec2::new(a.x, b.y)
+    };
+
+    dungeon.corridors.push(Corridor { from, to, corner });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn configs() -> Vec<DungeonConfig> {
+        vec![
+            DungeonConfig::default(),
+            DungeonConfig {
+                size: IVec2::new(120, 30),
+                min_room_size: 3,
+                padding: 2,
+                max_depth: 6,
+                extra_corridors: 0,
+            },
+            DungeonConfig {
+                size: IVec2::new(10, 10),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn rooms_are_connected() {
+        for config in configs() {
+            for seed in 0..50 {
+                let dungeon = config.generate(&mut Xoshiro256StarStar::seed_from_u64(seed));
+                assert!(!dungeon.rooms.is_empty());
+
+                let mut reached = vec![false; dungeon.rooms.len()];
+                let mut stack = vec![0];
+                reached[0] = true;
+                while let Some(room) = stack.pop() {
+                    for neighbour in dungeon.neighbours(room) {
+                        if !reached[neighbour] {
+                            reached[neighbour] = true;
+                            stack.push(neighbour);
+                        }
+                    }
+                }
+
+                assert!(reached.iter().all(|reached| *reached), "{:?}", dungeon);
+            }
+        }
+    }
+
+    #[test]
+    fn rooms_fit_and_never_touch() {
+        for config in configs() {
+            for seed in 0..50 {
+                let dungeon = config.generate(&mut Xoshiro256StarStar::seed_from_u64(seed));
+
+                for (index, room) in dungeon.rooms.iter().enumerate() {
+                    assert!(room.size.x >= config.min_room_size);
+                    assert!(room.size.y >= config.min_room_size);
+                    assert!(room.min.x >= config.padding && room.min.y >= config.padding);
+                    assert!(room.max().x <= config.size.x - config.padding);
+                    assert!(room.max().y <= config.size.y - config.padding);
+
+                    for other in &dungeon.rooms[index + 1..] {
+                        let apart = room.max().x < other.min.x
+                            || other.max().x < room.min.x
+                            || room.max().y < other.min.y
+                            || other.max().y < room.min.y;
+                        assert!(apart, "{:?} touches {:?}", room, other);
+                    }
+                }
+
+                for corridor in &dungeon.corridors {
+                    assert_ne!(corridor.from, corridor.to);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn too_small_for_a_room() {
+        let config = DungeonConfig {
+            size: IVec2::new(5, 20),
+            ..Default::default()
+        };
+
+        let dungeon = config.generate(&mut Xoshiro256StarStar::seed_from_u64(0));
+        assert_eq!(dungeon, Dungeon::default());
+    }
+
+    #[test]
+    fn only_depends_on_the_rng() {
+        let config = DungeonConfig::default();
+        assert_eq!(
+            config.generate(&mut Xoshiro256StarStar::seed_from_u64(7)),
+            config.generate(&mut Xoshiro256StarStar::seed_from_u64(7)),
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::bevy::prelude::*;
+use crate::RngIndexExt;
+use rand::Rng;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// An easing curve, mapping `t` in `[0, 1]` to a position in `[0, 1]`.
+///
+/// See [`RngEaseExt::between`] to shape random values with a curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ease {
+    /// No easing. This is the default.
+    Linear,
+
+    /// Starts slow, quadratically.
+    InQuad,
+
+    /// Ends slow, quadratically.
+    OutQuad,
+
+    /// Starts and ends slow, quadratically.
+    InOutQuad,
+
+    /// Starts slow, cubically.
+    InCubic,
+
+    /// Ends slow, cubically.
+    OutCubic,
+
+    /// Starts and ends slow, cubically.
+    InOutCubic,
+
+    /// Starts slow, along a sine wave.
+    InSine,
+
+    /// Ends slow, along a sine wave.
+    OutSine,
+
+    /// Starts and ends slow, along a sine wave.
+    InOutSine,
+}
+
+impl Default for Ease {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl Ease {
+    /// Every easing curve, in declaration order.
+    pub const ALL: [Ease; 10] = [
+        Self::Linear,
+        Self::InQuad,
+        Self::OutQuad,
+        Self::InOutQuad,
+        Self::InCubic,
+        Self::OutCubic,
+        Self::InOutCubic,
+        Self::InSine,
+        Self::OutSine,
+        Self::InOutSine,
+    ];
+
+    /// Apply the curve to `t`, which is clamped to `[0, 1]`.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::InQuad => t * t,
+            Self::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::InOutQuad if t < 0.5 => 2.0 * t * t,
+            Self::InOutQuad => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Self::InCubic => t * t * t,
+            Self::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::InOutCubic if t < 0.5 => 4.0 * t * t * t,
+            Self::InOutCubic => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Self::InSine => 1.0 - (t * PI / 2.0).cos(),
+            Self::OutSine => (t * PI / 2.0).sin(),
+            Self::InOutSine => -((t * PI).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+/// Values that can be linearly interpolated, see [`RngEaseExt::lerp`].
+pub trait Lerp: Copy {
+    /// Interpolate between `self` (at `t = 0`) and `other` (at `t = 1`).
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * f64::from(t)
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec4 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+/// Random interpolation and easing helpers, e.g. to vary procedural
+/// animations.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+///
+/// ```rust,ignore
+/// let offset = rng.lerp(Vec3::ZERO, Vec3::new(0.0, 0.5, 0.0));
+/// let delay = rng.between(0.1..0.4, Ease::InQuad);
+/// let ease = rng.pick_ease();
+/// ```
+pub trait RngEaseExt: Rng {
+    /// Returns a value between `a` and `b`, at a uniformly distributed `t`.
+    ///
+    /// Exactly one value is drawn, for every type.
+    fn lerp<T: Lerp>(&mut self, a: T, b: T) -> T {
+        a.lerp(b, self.gen())
+    }
+
+    /// Returns a value within the range, distributed by the easing curve:
+    /// `Ease::InQuad` favors the start of the range, `Ease::OutQuad` the
+    /// end.
+    ///
+    /// An empty range always produces its start. Exactly one value is drawn.
+    fn between<T: Lerp>(&mut self, range: Range<T>, ease: Ease) -> T {
+        range.start.lerp(range.end, ease.apply(self.gen()))
+    }
+
+    /// Returns one of the easing curves in [`Ease::ALL`], each equally
+    /// likely.
+    fn pick_ease(&mut self) -> Ease {
+        self.pick_ease_from(&Ease::ALL)
+    }
+
+    /// Returns one of the given easing curves, each equally likely, or
+    /// `Ease::Linear` if there are none.
+    fn pick_ease_from(&mut self, eases: &[Ease]) -> Ease {
+        self.index(eases.len())
+            .map_or(Ease::Linear, |index| eases[index])
+    }
+}
+
+impl<R: Rng + ?Sized> RngEaseExt for R {}
@@ -0,0 +1,230 @@
+use crate::bevy::prelude::*;
+use crate::{Rng, RootRng, Seed};
+use rand::Rng as _;
+use std::collections::BTreeMap;
+
+/// The label of the stream the [`EncounterScheduler`] draws from, by default.
+pub const ENCOUNTER_STREAM: &str = "encounters";
+
+/// The encounter settings of a zone, see [`EncounterScheduler`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncounterZone {
+    /// The probability of an encounter on each check, once the cooldown is
+    /// over.
+    pub rate: f32,
+
+    /// The number of checks after an encounter during which no encounter can
+    /// happen.
+    pub cooldown: u32,
+
+    /// The probability added to `rate` for every check without an encounter
+    /// after the cooldown, so long streaks without an encounter get less
+    /// likely.
+    pub escalation: f32,
+}
+
+impl EncounterZone {
+    /// Create a zone with the given rate, without cooldown or escalation.
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            cooldown: 0,
+            escalation: 0.0,
+        }
+    }
+
+    /// Set the cooldown, in checks.
+    pub fn with_cooldown(mut self, cooldown: u32) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Set the escalation per check.
+    pub fn with_escalation(mut self, escalation: f32) -> Self {
+        self.escalation = escalation;
+        self
+    }
+
+    /// The probability of an encounter after `checks` checks without one.
+    pub fn chance(&self, checks: u32) -> f32 {
+        match checks.checked_sub(self.cooldown) {
+            Some(escalated) => (self.rate + self.escalation * escalated as f32).clamp(0.0, 1.0),
+            None => 0.0,
+        }
+    }
+}
+
+/// The event sent when an [`EncounterScheduler`] check triggers an encounter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncounterTriggered {
+    /// The zone the encounter happened in.
+    pub zone: String,
+
+    /// The number of checks since the previous encounter, including the
+    /// current one.
+    pub checks: u32,
+}
+
+/// The `EncounterScheduler` resource rolls random encounters, JRPG-style.
+///
+/// Register the zones of your game with their [`EncounterZone`] settings,
+/// [`enter`](EncounterScheduler::enter) the zone the player is in, and call
+/// [`check`](EncounterScheduler::check) whenever an encounter could happen,
+/// e.g. for every step the player takes. Checks are rolled in order by the
+/// system added by the [`EncounterPlugin`], which sends an
+/// [`EncounterTriggered`] event for each encounter.
+///
+/// ```rust,ignore
+/// fn setup(mut encounters: ResMut<EncounterScheduler>) {
+///     encounters
+///         .add_zone("forest", EncounterZone::new(0.05).with_cooldown(8))
+///         .add_zone("cave", EncounterZone::new(0.1).with_escalation(0.01))
+///         .enter("forest");
+/// }
+/// ```
+///
+/// The scheduler draws from the stream labelled [`ENCOUNTER_STREAM`], so the
+/// encounters of a run only depend on the seed and the sequence of checks.
+/// Checks made outside of any registered zone never trigger an encounter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncounterScheduler {
+    zones: BTreeMap<String, EncounterZone>,
+    zone: Option<String>,
+    pending: u32,
+    checks: u32,
+    rng: Rng,
+}
+
+impl EncounterScheduler {
+    /// Create a scheduler without zones, drawing from the given rng.
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            zones: BTreeMap::new(),
+            zone: None,
+            pending: 0,
+            checks: 0,
+            rng,
+        }
+    }
+
+    /// Register a zone, or replace the settings of an existing one.
+    pub fn add_zone(&mut self, name: impl Into<String>, zone: EncounterZone) -> &mut Self {
+        self.zones.insert(name.into(), zone);
+        self
+    }
+
+    /// Returns the settings of the zone with the given name.
+    pub fn get_zone(&self, name: &str) -> Option<&EncounterZone> {
+        self.zones.get(name)
+    }
+
+    /// Enter the zone with the given name. Checks already made are rolled in
+    /// the new zone.
+    ///
+    /// The number of checks since the last encounter carries over, so
+    /// moving between zones doesn't reset the cooldown or the escalation.
+    pub fn enter(&mut self, name: impl Into<String>) -> &mut Self {
+        self.zone = Some(name.into());
+        self
+    }
+
+    /// Leave the current zone: no encounter happens until another zone is
+    /// entered.
+    pub fn leave(&mut self) -> &mut Self {
+        self.zone = None;
+        self
+    }
+
+    /// The name of the current zone.
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    /// Request an encounter check, rolled by the next run of the system
+    /// added by the [`EncounterPlugin`].
+    pub fn check(&mut self) {
+        self.pending += 1;
+    }
+
+    /// The number of checks since the last encounter.
+    pub fn checks(&self) -> u32 {
+        self.checks
+    }
+
+    /// The probability that the next check triggers an encounter.
+    pub fn chance(&self) -> f32 {
+        self.current_zone()
+            .map_or(0.0, |zone| zone.chance(self.checks + 1))
+    }
+
+    /// Start over as if an encounter just happened, e.g. after a scripted
+    /// battle.
+    pub fn reset(&mut self) {
+        self.checks = 0;
+    }
+
+    /// Roll every pending check, and return the encounters they triggered.
+    pub fn roll(&mut self) -> Vec<EncounterTriggered> {
+        let mut encounters = Vec::new();
+        let pending = std::mem::take(&mut self.pending);
+        let (name, zone) = match self.zone.clone().zip(self.current_zone().copied()) {
+            Some(current) => current,
+            None => return encounters,
+        };
+
+        for _ in 0..pending {
+            self.checks += 1;
+            let chance = zone.chance(self.checks);
+            if chance > 0.0 && self.rng.gen::<f32>() < chance {
+                encounters.push(EncounterTriggered {
+                    zone: name.clone(),
+                    checks: self.checks,
+                });
+                self.checks = 0;
+            }
+        }
+
+        encounters
+    }
+
+    fn current_zone(&self) -> Option<&EncounterZone> {
+        self.zone.as_ref().and_then(|zone| self.zones.get(zone))
+    }
+}
+
+impl FromWorld for EncounterScheduler {
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world.get_resource::<RootRng>() {
+            Some(root) => root.stream_rng(ENCOUNTER_STREAM),
+            None => Seed::from_entropy().stream_rng(ENCOUNTER_STREAM),
+        };
+
+        Self::new(rng)
+    }
+}
+
+/// Adds the [`EncounterScheduler`] resource, and sends an
+/// [`EncounterTriggered`] event for every encounter it rolls.
+///
+/// Add it after the [`RngPlugin`](crate::RngPlugin), so the scheduler draws
+/// from the seeded stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EncounterPlugin;
+
+impl Plugin for EncounterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<EncounterScheduler>()
+            .add_event::<EncounterTriggered>()
+            .add_system(roll_encounters.system());
+    }
+}
+
+fn roll_encounters(
+    mut scheduler: ResMut<EncounterScheduler>,
+    mut encounters: EventWriter<EncounterTriggered>,
+) {
+    for encounter in scheduler.roll() {
+        encounters.send(encounter);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::index::below;
+use rand::RngCore;
+use std::ops::Range;
+
+/// The number of fractional bits of the fixed-point values of
+/// [`RngFixedExt`].
+pub const FIXED_FRACTION_BITS: u32 = 16;
+
+/// `1.0` as a fixed-point value of [`RngFixedExt`].
+pub const FIXED_ONE: i64 = 1 << FIXED_FRACTION_BITS;
+
+/// Draw random values without any floating point, e.g. for lockstep
+/// simulations that ban floats because they may round differently across
+/// platforms and compilers.
+///
+/// Every method only uses integer arithmetic on the values drawn as 64-bit
+/// integers, so the results are the same on every platform. Fixed-point
+/// values have [`FIXED_FRACTION_BITS`] fractional bits, so [`FIXED_ONE`]
+/// stands for `1.0`.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng).
+pub trait RngFixedExt: RngCore {
+    /// Returns a uniformly distributed integer in the range.
+    ///
+    /// Like [`RngIndexExt::index`](crate::RngIndexExt::index), this is free
+    /// of modulo bias, and usually draws a single value.
+    ///
+    /// # Panics
+    ///
+    /// If the range is empty.
+    fn range_i64(&mut self, range: Range<i64>) -> i64 {
+        assert!(range.start < range.end, "cannot sample an empty range");
+
+        let span = range.end.wrapping_sub(range.start) as u64;
+        range.start.wrapping_add(below(self, span) as i64)
+    }
+
+    /// Returns `true` with a probability of `numerator / denominator`, or
+    /// never if the denominator is zero.
+    ///
+    /// Draws one value, except for rare rejections, whatever the ratio.
+    fn chance_ratio(&mut self, numerator: u64, denominator: u64) -> bool {
+        if denominator == 0 {
+            self.next_u64();
+            return false;
+        }
+
+        below(self, denominator) < numerator
+    }
+
+    /// Returns `true` with a probability of `permille / 1000`, e.g. `250`
+    /// for a 25% chance. Values of `1000` and above always succeed.
+    fn chance_permille(&mut self, permille: u32) -> bool {
+        self.chance_ratio(u64::from(permille), 1000)
+    }
+
+    /// Returns a fixed-point value uniformly distributed in
+    /// `[0, FIXED_ONE)`.
+    fn fixed_01(&mut self) -> i64 {
+        (self.next_u64() >> (64 - FIXED_FRACTION_BITS)) as i64
+    }
+
+    /// Returns a value between `a` and `b`, at a random fixed-point position
+    /// drawn with [`fixed_01`](RngFixedExt::fixed_01), rounded down.
+    ///
+    /// `a` and `b` can be integers or fixed-point values alike.
+    fn lerp_fixed(&mut self, a: i64, b: i64) -> i64 {
+        let t = self.fixed_01();
+        let offset = (i128::from(b) - i128::from(a)) * i128::from(t);
+        (i128::from(a) + (offset >> FIXED_FRACTION_BITS)) as i64
+    }
+}
+
+impl<R: RngCore + ?Sized> RngFixedExt for R {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RngIndexExt;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn range_i64_stays_in_range() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..1000 {
+            assert!((-3..4).contains(&rng.range_i64(-3..4)));
+        }
+
+        rng.range_i64(i64::MIN..i64::MAX);
+        assert_eq!(rng.range_i64(5..6), 5);
+    }
+
+    #[test]
+    fn chances_at_the_bounds() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..1000 {
+            assert!(!rng.chance_permille(0));
+            assert!(rng.chance_permille(1000));
+            assert!(!rng.chance_ratio(1, 0));
+        }
+    }
+
+    #[test]
+    fn matches_index() {
+        let mut a = Xoshiro256StarStar::seed_from_u64(2);
+        let mut b = a.clone();
+        for len in 1..100 {
+            assert_eq!(a.range_i64(0..len as i64), b.index(len).unwrap() as i64);
+        }
+    }
+
+    #[test]
+    fn lerp_fixed_stays_between() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(3);
+        for _ in 0..1000 {
+            let t = rng.fixed_01();
+            assert!((0..FIXED_ONE).contains(&t));
+
+            assert!((-10..=20).contains(&rng.lerp_fixed(-10, 20)));
+            assert!((-10..=20).contains(&rng.lerp_fixed(20, -10)));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::RngIndexExt;
+use rand::Rng;
+
+/// A set of flags that can be randomly generated with
+/// [`RngFlagsExt::flags`].
+///
+/// This is a small bridge to implement for your flag types, e.g. those
+/// generated by the `bitflags` crate:
+///
+/// ```rust,ignore
+/// impl Bitflags for Affixes {
+///     const ALL: u64 = Affixes::all().bits() as u64;
+///
+///     fn from_bits(bits: u64) -> Self {
+///         Affixes::from_bits_truncate(bits as _)
+///     }
+/// }
+/// ```
+pub trait Bitflags: Sized {
+    /// The bits of every defined flag.
+    const ALL: u64;
+
+    /// Create the flags from bits, which only ever contain bits of
+    /// [`Bitflags::ALL`].
+    fn from_bits(bits: u64) -> Self;
+}
+
+/// Randomly toggle flags with a controlled density.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+pub trait RngFlagsExt: Rng {
+    /// Returns flags where each defined flag is set independently with
+    /// probability `density`, clamped to `[0, 1]`.
+    ///
+    /// Draws exactly one value for every defined flag, regardless of the
+    /// density, so tuning the density doesn't shift later draws.
+    fn flags<T: Bitflags>(&mut self, density: f32) -> T {
+        let mut bits = 0;
+
+        for bit in (0..64).map(|bit| 1 << bit).filter(|bit| T::ALL & bit != 0) {
+            if self.gen::<f32>() < density {
+                bits |= bit;
+            }
+        }
+
+        T::from_bits(bits)
+    }
+
+    /// Returns a mask with exactly `bits_set` random bits set (all of them if
+    /// `bits_set` is 32 or more), every such mask being equally likely.
+    fn mask_u32(&mut self, bits_set: u32) -> u32 {
+        let bits_set = bits_set.min(32);
+        let mut mask = 0;
+
+        // Floyd's algorithm: picks `bits_set` distinct bits with one draw each.
+        for bit in 32 - bits_set..32 {
+            let candidate = self.index(bit as usize + 1).expect("length is non-zero") as u32;
+
+            if mask & (1 << candidate) == 0 {
+                mask |= 1 << candidate;
+            } else {
+                mask |= 1 << bit;
+            }
+        }
+
+        mask
+    }
+}
+
+impl<R: Rng + ?Sized> RngFlagsExt for R {}
@@ -0,0 +1,35 @@
+use rand::RngCore;
+
+/// Fast helpers to draw canonical floats.
+///
+/// These fill the mantissa of a float in `[1, 2)` with random bits and
+/// subtract `1`, which is cheaper than the generic `gen_range` path. The
+/// results are uniformly distributed on an evenly spaced grid: 2^23 possible
+/// values for `f32` and 2^52 for `f64`, so the smallest non-zero result is
+/// `2^-23` (resp. `2^-52`), and values closer to zero than that never occur.
+/// That is plenty for gameplay and visuals, but use `gen` when you need the
+/// full precision near zero.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng). Each call draws exactly one value from the rng.
+pub trait RngFloatExt: RngCore {
+    /// Returns a float in `[0, 1)`.
+    fn f32_01(&mut self) -> f32 {
+        f32::from_bits(0x3f80_0000 | (self.next_u32() >> 9)) - 1.0
+    }
+
+    /// Returns a float in `[0, 1)`.
+    fn f64_01(&mut self) -> f64 {
+        f64::from_bits(0x3ff0_0000_0000_0000 | (self.next_u64() >> 12)) - 1.0
+    }
+
+    /// Returns a float in `[-1, 1)`, using a float in `[2, 4)`.
+    ///
+    /// The grid is twice as coarse as the one of [`RngFloatExt::f32_01`]: the
+    /// spacing between possible values is `2^-22`.
+    fn snorm(&mut self) -> f32 {
+        f32::from_bits(0x4000_0000 | (self.next_u32() >> 9)) - 3.0
+    }
+}
+
+impl<R: RngCore + ?Sized> RngFloatExt for R {}
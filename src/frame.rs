@@ -0,0 +1,69 @@
+use crate::bevy::prelude::*;
+use crate::counter::CounterRng;
+use crate::{hash, Seed};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng as _;
+use std::hash::Hash;
+
+/// The `FrameRng` resource hands out values that stay the same for an entire
+/// frame.
+///
+/// Any number of systems asking for the value of the same key during a frame
+/// get the same result, without having to pass data around. A new value is
+/// computed for the next frame.
+///
+/// ```rust,ignore
+/// fn wind(frame_rng: Res<FrameRng>) {
+///     let gust: f32 = frame_rng.frame_value("wind gust strength");
+/// }
+/// ```
+///
+/// Values are derived from the seed, the key and the frame number, so they are
+/// just as reproducible as the seed itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrameRng {
+    seed: u64,
+    frame: u64,
+}
+
+impl FrameRng {
+    pub(crate) fn new(seed: &Seed) -> Self {
+        Self {
+            seed: hash::combine(seed.key(), hash::hash_bytes(b"frame")),
+            frame: 0,
+        }
+    }
+
+    /// Switch to a new seed, keeping the frame count.
+    pub(crate) fn reseed(&mut self, seed: &Seed) {
+        *self = Self {
+            frame: self.frame,
+            ..Self::new(seed)
+        };
+    }
+
+    pub(crate) fn set_frame(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    pub(crate) fn advance(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// The number of frames that have completed since the plugin was added.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns this frame's value for the given key.
+    pub fn frame_value<T>(&self, key: impl Hash) -> T
+    where
+        Standard: Distribution<T>,
+    {
+        CounterRng::new(self.seed, hash::hash_one(&key), self.frame).gen()
+    }
+}
+
+pub(crate) fn advance_frame(mut frame_rng: ResMut<FrameRng>) {
+    frame_rng.advance();
+}
@@ -0,0 +1,309 @@
+use crate::{RngFloatExt, RngIndexExt};
+use rand::RngCore;
+
+/// An undirected graph without loops or parallel edges, as adjacency lists,
+/// e.g. the nodes of an overworld map or a tech tree.
+///
+/// Generate one with [`Graph::erdos_renyi`], [`Graph::watts_strogatz`] or
+/// [`Graph::spanning_tree`]. The graphs only depend on the values drawn from
+/// the rng, and the generators only use [`RngIndexExt::index`] and
+/// [`RngFloatExt::f64_01`], so the same seed produces the same graph across
+/// releases of `rand`.
+///
+/// Nodes are numbered from zero, and the neighbors of each node are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// Create a graph of `nodes` nodes without edges.
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            adjacency: vec![vec![]; nodes],
+        }
+    }
+
+    /// Connect every pair of nodes with probability `p`.
+    ///
+    /// Draws one value per pair of nodes.
+    pub fn erdos_renyi<R: RngCore + ?Sized>(rng: &mut R, nodes: usize, p: f64) -> Self {
+        let mut graph = Self::new(nodes);
+        for a in 0..nodes {
+            for b in a + 1..nodes {
+                if rng.f64_01() < p {
+                    graph.add_edge(a, b);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Create a small world: a ring where each node is connected to its `k`
+    /// nearest neighbors, `k / 2` on each side, and each of those edges is
+    /// rewired to a random node with probability `beta`.
+    ///
+    /// Low probabilities keep most of the ring, with a few shortcuts across
+    /// it, which is what makes distances short while neighborhoods stay
+    /// tight. `k` is rounded down to an even number, and kept below the
+    /// number of nodes.
+    pub fn watts_strogatz<R: RngCore + ?Sized>(
+        rng: &mut R,
+        nodes: usize,
+        k: usize,
+        beta: f64,
+    ) -> Self {
+        let mut graph = Self::new(nodes);
+        let half = k.min(nodes.saturating_sub(1)) / 2;
+        for a in 0..nodes {
+            for offset in 1..=half {
+                graph.add_edge(a, (a + offset) % nodes);
+            }
+        }
+
+        for a in 0..nodes {
+            for offset in 1..=half {
+                let b = (a + offset) % nodes;
+                if rng.f64_01() >= beta || graph.degree(a) + 1 >= nodes {
+                    continue;
+                }
+
+                // Pick among the nodes `a` isn't connected to yet.
+                let free = nodes - 1 - graph.degree(a);
+                let skip = rng.index(free).unwrap_or(0);
+                let target = (0..nodes)
+                    .filter(|&other| other != a && !graph.has_edge(a, other))
+                    .nth(skip);
+
+                if let Some(target) = target {
+                    graph.remove_edge(a, b);
+                    graph.add_edge(a, target);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Create a random tree connecting every node, then add `extra_edges`
+    /// edges between random nodes, to create loops.
+    ///
+    /// Each node is attached to a random node numbered before it, so node `0`
+    /// is the root and low numbers tend to have more neighbors, like the
+    /// start of a node map. Fewer extra edges are added when the graph
+    /// becomes complete.
+    pub fn spanning_tree<R: RngCore + ?Sized>(
+        rng: &mut R,
+        nodes: usize,
+        extra_edges: usize,
+    ) -> Self {
+        let mut graph = Self::new(nodes);
+        for node in 1..nodes {
+            let parent = rng.index(node).unwrap_or(0);
+            graph.add_edge(parent, node);
+        }
+
+        let max_edges = nodes * nodes.saturating_sub(1) / 2;
+        let mut added = 0;
+        while added < extra_edges && graph.edge_count() < max_edges {
+            let a = rng.index(nodes).unwrap_or(0);
+            let b = rng.index(nodes).unwrap_or(0);
+            if graph.add_edge(a, b) {
+                added += 1;
+            }
+        }
+
+        graph
+    }
+
+    /// The number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// The number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.adjacency.iter().map(Vec::len).sum::<usize>() / 2
+    }
+
+    /// Returns the neighbors of the node, in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// If the node doesn't exist.
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    /// The number of neighbors of the node.
+    ///
+    /// # Panics
+    ///
+    /// If the node doesn't exist.
+    pub fn degree(&self, node: usize) -> usize {
+        self.adjacency[node].len()
+    }
+
+    /// Returns the adjacency lists of all nodes.
+    pub fn adjacency(&self) -> &[Vec<usize>] {
+        &self.adjacency
+    }
+
+    /// Returns `true` if the nodes are connected.
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.adjacency
+            .get(a)
+            .map_or(false, |neighbors| neighbors.binary_search(&b).is_ok())
+    }
+
+    /// Returns every edge once, as `(a, b)` with `a < b`, in ascending order.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(a, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |&&b| a < b)
+                    .map(move |&b| (a, b))
+            })
+    }
+
+    /// Connect the nodes, returning `false` if they already were, if they are
+    /// the same node, or if either doesn't exist.
+    pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+        if a == b || a >= self.adjacency.len() || b >= self.adjacency.len() {
+            return false;
+        }
+
+        match self.adjacency[a].binary_search(&b) {
+            Ok(_) => false,
+            Err(index) => {
+                self.adjacency[a].insert(index, b);
+                let index = self.adjacency[b].binary_search(&a).unwrap_err();
+                self.adjacency[b].insert(index, a);
+                true
+            }
+        }
+    }
+
+    /// Disconnect the nodes, returning `false` if they weren't connected.
+    pub fn remove_edge(&mut self, a: usize, b: usize) -> bool {
+        if !self.has_edge(a, b) {
+            return false;
+        }
+
+        self.adjacency[a].retain(|&other| other != b);
+        self.adjacency[b].retain(|&other| other != a);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn assert_valid(graph: &Graph) {
+        for (a, neighbors) in graph.adjacency().iter().enumerate() {
+            assert!(neighbors.windows(2).all(|pair| pair[0] < pair[1]));
+            for &b in neighbors {
+                assert!(b < graph.node_count() && b != a);
+                assert!(graph.has_edge(b, a));
+            }
+        }
+
+        assert_eq!(graph.edges().count(), graph.edge_count());
+    }
+
+    fn is_connected(graph: &Graph) -> bool {
+        let mut reached = vec![false; graph.node_count()];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            if !std::mem::replace(&mut reached[node], true) {
+                stack.extend_from_slice(graph.neighbors(node));
+            }
+        }
+
+        reached.iter().all(|reached| *reached)
+    }
+
+    #[test]
+    fn erdos_renyi_bounds() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for nodes in 0..12 {
+            assert_eq!(Graph::erdos_renyi(&mut rng, nodes, 0.0).edge_count(), 0);
+
+            let complete = Graph::erdos_renyi(&mut rng, nodes, 1.0);
+            assert_eq!(complete.edge_count(), nodes * nodes.saturating_sub(1) / 2);
+            assert_valid(&complete);
+
+            assert_valid(&Graph::erdos_renyi(&mut rng, nodes, 0.3));
+        }
+    }
+
+    #[test]
+    fn watts_strogatz_without_rewiring_is_a_ring() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(2);
+        let graph = Graph::watts_strogatz(&mut rng, 10, 4, 0.0);
+        assert_valid(&graph);
+
+        for a in 0..10 {
+            assert_eq!(graph.neighbors(a).len(), 4);
+            for offset in [1, 2, 8, 9].iter() {
+                assert!(graph.has_edge(a, (a + offset) % 10));
+            }
+        }
+    }
+
+    #[test]
+    fn watts_strogatz_keeps_the_edge_count() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(3);
+        for nodes in 0..12 {
+            for k in 0..14 {
+                for beta in [0.2, 1.0].iter() {
+                    let graph = Graph::watts_strogatz(&mut rng, nodes, k, *beta);
+                    let half = k.min(nodes.saturating_sub(1)) / 2;
+                    assert_valid(&graph);
+                    assert_eq!(graph.edge_count(), nodes * half);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spanning_trees_connect_every_node() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(4);
+        for nodes in 1..12 {
+            let tree = Graph::spanning_tree(&mut rng, nodes, 0);
+            assert_valid(&tree);
+            assert!(is_connected(&tree));
+            assert_eq!(tree.edge_count(), nodes - 1);
+
+            let looped = Graph::spanning_tree(&mut rng, nodes, 3);
+            let max_edges = nodes * (nodes - 1) / 2;
+            assert_valid(&looped);
+            assert!(is_connected(&looped));
+            assert_eq!(looped.edge_count(), (nodes - 1 + 3).min(max_edges));
+        }
+
+        assert_eq!(Graph::spanning_tree(&mut rng, 0, 5), Graph::new(0));
+    }
+
+    #[test]
+    fn edges_are_unique() {
+        let mut graph = Graph::new(3);
+        assert!(graph.add_edge(2, 0));
+        assert!(!graph.add_edge(0, 2));
+        assert!(!graph.add_edge(1, 1));
+        assert!(!graph.add_edge(1, 3));
+        assert_eq!(graph.edges().collect::<Vec<_>>(), vec![(0, 2)]);
+
+        assert!(graph.remove_edge(0, 2));
+        assert!(!graph.remove_edge(2, 0));
+        assert_eq!(graph, Graph::new(3));
+    }
+}
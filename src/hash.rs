@@ -0,0 +1,209 @@
+//! Small, stable hashing primitives used to derive keys from seeds.
+//!
+//! These are deliberately implemented in-crate, so derived values never change
+//! with the platform or with upgrades of the standard library.
+
+use crate::bevy::prelude::*;
+use std::hash::{Hash, Hasher};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The SplitMix64 finalizer, a fast bijective 64-bit mixer.
+pub(crate) const fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Hashes a byte slice into a well-mixed `u64` (FNV-1a, then [`mix64`]).
+pub(crate) const fn hash_bytes(bytes: &[u8]) -> u64 {
+    mix64(fnv(FNV_OFFSET, bytes))
+}
+
+/// Absorbs the bytes into an FNV-1a state.
+const fn fnv(mut state: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        state = (state ^ bytes[i] as u64).wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+
+    state
+}
+
+/// Hashes any `Hash` value with the [`StableHasher`].
+pub(crate) fn hash_one<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A `Hasher` producing the same output on every platform.
+///
+/// The input is hashed with 64-bit FNV-1a, and the final state is passed
+/// through the SplitMix64 finalizer. Integers are always written as
+/// little-endian bytes, and `usize`/`isize` are widened to 64 bits, so neither
+/// endianness nor pointer width affect the result.
+///
+/// This algorithm is part of the crate's stability guarantees: it won't
+/// change without a major version bump. It can't protect against `Hash`
+/// implementations that change what they feed to the hasher, so prefer
+/// hashing your own types (or plain integers and strings) when the result
+/// must never change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self { state: FNV_OFFSET }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        mix64(self.state)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.state = fnv(self.state, bytes);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16)
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32)
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64)
+    }
+}
+
+/// Absorbs a byte slice into a 256-bit generator seed.
+///
+/// Unlike [`hash_bytes`], the input isn't squeezed through 64 bits first:
+/// every byte contributes to the full state, so seeds of up to 32 bytes keep
+/// all of their entropy.
+pub(crate) const fn seed_bytes(bytes: &[u8]) -> [u8; 32] {
+    absorb([mix64(1), mix64(2), mix64(3), mix64(4)], bytes)
+}
+
+/// Derives a 256-bit generator seed from a string seed.
+///
+/// This is version 1 of the string seed derivation: the UTF-8 bytes of the
+/// string are absorbed like [`seed_bytes`], starting from a state tagged with
+/// the version, so string and byte seeds never collide. It only depends on
+/// the bytes of the string, so a seed string produces the same generator on
+/// every platform, and the derivation won't change without a major version
+/// bump.
+pub(crate) const fn seed_string(seed: &str) -> [u8; 32] {
+    let tag = hash_bytes(b"string-v1");
+    let lanes = [
+        combine(tag, 1),
+        combine(tag, 2),
+        combine(tag, 3),
+        combine(tag, 4),
+    ];
+
+    absorb(lanes, seed.as_bytes())
+}
+
+// Written with `while` loops, so seeds can be computed in const contexts, see
+// the `seed!` macro.
+const fn absorb(mut lanes: [u64; 4], bytes: &[u8]) -> [u8; 32] {
+    // Every 8 bytes form a little-endian word, the last one zero-padded.
+    let mut i = 0;
+    while i < bytes.len() {
+        let mut word = 0;
+        let mut j = 0;
+        while j < 8 && i + j < bytes.len() {
+            word |= (bytes[i + j] as u64) << (8 * j);
+            j += 1;
+        }
+
+        let lane = (i / 8) % 4;
+        lanes[lane] = mix64(lanes[lane] ^ word);
+        i += 8;
+    }
+
+    // The length tells apart inputs that only differ by trailing zeros.
+    lanes[0] ^= bytes.len() as u64;
+    let mut round = 0;
+    while round < 2 {
+        let mut lane = 0;
+        while lane < 4 {
+            lanes[lane] = combine(lanes[lane], lanes[(lane + 1) % 4]);
+            lane += 1;
+        }
+
+        round += 1;
+    }
+
+    let mut seed = [0; 32];
+    let mut i = 0;
+    while i < 32 {
+        seed[i] = (lanes[i / 8] >> (8 * (i % 8))) as u8;
+        i += 1;
+    }
+
+    seed
+}
+
+/// Combines two keys into one. The order of the arguments matters.
+pub(crate) const fn combine(a: u64, b: u64) -> u64 {
+    mix64(a ^ mix64(b).wrapping_add(GOLDEN_GAMMA))
+}
+
+/// Packs the coordinates of a grid cell into one key, `x` in the high bits.
+///
+/// Every layout derived from cells (chunks, biomes, path costs) goes through
+/// this, so changing it changes all of them.
+pub(crate) fn cell_key(cell: IVec2) -> u64 {
+    u64::from(cell.x as u32) << 32 | u64::from(cell.y as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_keys_are_pinned() {
+        assert_eq!(cell_key(IVec2::new(0, 0)), 0);
+        assert_eq!(cell_key(IVec2::new(1, -1)), 0x0000_0001_ffff_ffff);
+        assert_eq!(cell_key(IVec2::new(-1, 2)), 0xffff_ffff_0000_0002);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::bevy::prelude::*;
+use crate::Rng;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::panic::{self, Location};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The number of rolls kept per stream by default.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 64;
+
+/// A roll recorded by a [`RollHistory`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RollRecord {
+    /// The label given to the roll.
+    pub label: &'static str,
+
+    /// Where the roll was made.
+    pub location: &'static Location<'static>,
+
+    /// The number of values drawn from the stream before the roll.
+    pub draw: u64,
+
+    /// The number of values the roll drew.
+    pub draws: u64,
+
+    /// The outcome of the roll, formatted with `Debug`.
+    pub outcome: String,
+}
+
+impl fmt::Display for RollRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "draw #{} {} at {}: {}",
+            self.draw, self.label, self.location, self.outcome
+        )
+    }
+}
+
+/// The `RollHistory` resource keeps the last rolls of each stream, to see
+/// what actually came out when a player reports a bug.
+///
+/// Rolls are only recorded where you make them through
+/// [`RollHistory::roll`], so the history costs nothing elsewhere:
+///
+/// ```rust,ignore
+/// fn drop_loot(history: Res<RollHistory>, mut rng: Local<StreamRng<Loot>>) {
+///     if history.roll(&mut rng, "boss key", |rng| rng.gen_bool(0.25)) {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Each stream keeps the last [`RollHistory::capacity`] rolls, and the
+/// `Display` implementation lists all of them, stream by stream. Add the
+/// [`RollHistoryPlugin`] to insert the resource, and optionally print the
+/// history when the app panics.
+///
+/// Clones share the same history.
+#[derive(Debug, Clone)]
+pub struct RollHistory {
+    shared: Arc<Mutex<History>>,
+}
+
+#[derive(Debug)]
+struct History {
+    capacity: usize,
+    streams: BTreeMap<Option<String>, VecDeque<RollRecord>>,
+}
+
+impl RollHistory {
+    /// Create a history keeping up to `capacity` rolls per stream, at least
+    /// one.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(History {
+                capacity: capacity.max(1),
+                streams: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// The number of rolls kept per stream.
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Make the roll, and record its outcome with the label, the call site
+    /// and the draws it made.
+    #[track_caller]
+    pub fn roll<T, F>(&self, rng: &mut Rng, label: &'static str, roll: F) -> T
+    where
+        T: fmt::Debug,
+        F: FnOnce(&mut Rng) -> T,
+    {
+        let location = Location::caller();
+        let before = rng.seed_info();
+        let outcome = roll(rng);
+        let after = rng.seed_info();
+
+        // A reseed during the roll restarts the count.
+        let draws = after.draws.saturating_sub(before.draws);
+
+        self.record(
+            after.stream,
+            RollRecord {
+                label,
+                location,
+                draw: after.draws - draws,
+                draws,
+                outcome: format!("{:?}", outcome),
+            },
+        );
+
+        outcome
+    }
+
+    /// Returns the recorded rolls of the stream, `None` for unnamed rngs,
+    /// from the oldest to the newest.
+    pub fn rolls(&self, stream: Option<&str>) -> Vec<RollRecord> {
+        self.lock()
+            .streams
+            .get(&stream.map(str::to_owned))
+            .map(|rolls| rolls.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the names of the streams with recorded rolls, `None` for
+    /// unnamed rngs.
+    pub fn streams(&self) -> Vec<Option<String>> {
+        self.lock().streams.keys().cloned().collect()
+    }
+
+    /// Forget every recorded roll.
+    pub fn clear(&self) {
+        self.lock().streams.clear();
+    }
+
+    fn record(&self, stream: Option<String>, record: RollRecord) {
+        let mut history = self.lock();
+        let capacity = history.capacity;
+
+        let rolls = history.streams.entry(stream).or_default();
+        if rolls.len() == capacity {
+            rolls.pop_front();
+        }
+
+        rolls.push_back(record);
+    }
+
+    /// Locks the history, even if a panic poisoned it, so it can still be
+    /// printed from the panic hook.
+    fn lock(&self) -> MutexGuard<'_, History> {
+        self.shared
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for RollHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl fmt::Display for RollHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stream, rolls) in &self.lock().streams {
+            match stream {
+                Some(stream) => writeln!(f, "stream {}:", stream)?,
+                None => writeln!(f, "unnamed stream:")?,
+            }
+
+            for roll in rolls {
+                writeln!(f, "  {}", roll)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds the [`RollHistory`] resource.
+///
+/// ```rust,ignore
+/// app.add_plugin(RollHistoryPlugin::new(128).dump_on_panic());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RollHistoryPlugin {
+    capacity: usize,
+    dump_on_panic: bool,
+}
+
+impl RollHistoryPlugin {
+    /// Keep up to `capacity` rolls per stream.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            dump_on_panic: false,
+        }
+    }
+
+    /// Print the history to stderr when the app panics, after the message
+    /// of the panic.
+    ///
+    /// This installs a panic hook, which calls the hook that was installed
+    /// before.
+    pub fn dump_on_panic(mut self) -> Self {
+        self.dump_on_panic = true;
+        self
+    }
+}
+
+impl Default for RollHistoryPlugin {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl Plugin for RollHistoryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let history = RollHistory::new(self.capacity);
+
+        if self.dump_on_panic {
+            let dumped = history.clone();
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                previous(info);
+                eprintln!("recent rolls:\n{}", dumped);
+            }));
+        }
+
+        app.insert_resource(history);
+    }
+}
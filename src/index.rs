@@ -0,0 +1,50 @@
+use rand::RngCore;
+
+/// Pick random indices without modulo bias.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng).
+pub trait RngIndexExt: RngCore {
+    /// Returns a uniformly distributed index in `[0, len)`, or `None` if `len`
+    /// is zero.
+    ///
+    /// Uses Lemire's multiply-and-reject method: unlike `next_u64() % len`,
+    /// every index is exactly equally likely. Rejections are rare (less than
+    /// one in 2^32 draws for lengths below 2^32), so this usually draws a
+    /// single value.
+    ///
+    /// Values are always drawn as 64-bit integers, so the result doesn't
+    /// depend on the pointer width of the platform.
+    fn index(&mut self, len: usize) -> Option<usize> {
+        match len as u64 {
+            0 => None,
+            len => Some(below(self, len) as usize),
+        }
+    }
+}
+
+impl<R: RngCore + ?Sized> RngIndexExt for R {}
+
+/// Returns a uniformly distributed integer in `[0, n)`, with Lemire's
+/// multiply-and-reject method. `n` must not be zero.
+///
+/// Everything that promises unbiased integers goes through this, so they all
+/// draw the same values on every platform.
+pub(crate) fn below<R: RngCore + ?Sized>(rng: &mut R, n: u64) -> u64 {
+    let mut product = u128::from(rng.next_u64()) * u128::from(n);
+    if (product as u64) < n {
+        let threshold = n.wrapping_neg() % n;
+        while (product as u64) < threshold {
+            product = u128::from(rng.next_u64()) * u128::from(n);
+        }
+    }
+
+    (product >> 64) as u64
+}
+
+/// Scales `value` to `[0, n)` with a single multiply-shift, for callers that
+/// must draw exactly one value: unlike [`below`] this never redraws, at the
+/// cost of a bias below 2^-32 for `n` below 2^32.
+pub(crate) fn scale(value: u64, n: u64) -> u64 {
+    ((u128::from(value) * u128::from(n)) >> 64) as u64
+}
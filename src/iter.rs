@@ -0,0 +1,86 @@
+use crate::bevy::prelude::*;
+use crate::{CounterRng, StableId};
+use rand::RngCore;
+use std::vec;
+
+/// A key to sort items by before drawing from them, see
+/// [`RngIterExt::iter_deterministic`].
+pub trait SortKey: Ord {
+    /// The number the rng of the item is derived from.
+    fn stable_key(&self) -> u64;
+}
+
+impl SortKey for StableId {
+    fn stable_key(&self) -> u64 {
+        self.key()
+    }
+}
+
+impl SortKey for u64 {
+    fn stable_key(&self) -> u64 {
+        *self
+    }
+}
+
+/// Sorting by `Entity` is only deterministic as long as entities are spawned
+/// in the same order every time, prefer a [`StableId`] where possible.
+impl SortKey for Entity {
+    fn stable_key(&self) -> u64 {
+        self.to_bits()
+    }
+}
+
+/// Draw random values while iterating over entities.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng).
+pub trait RngIterExt: RngCore {
+    /// Returns the items sorted by key, each paired with an rng of its own.
+    ///
+    /// Queries don't iterate in a stable order, so drawing from one rng while
+    /// iterating hands out values differently from run to run. Here, the rng
+    /// of an item only depends on its key and on the state of this rng, not
+    /// on the other items, and items with side effects on each other are
+    /// processed in the same order every time:
+    ///
+    /// ```rust,ignore
+    /// fn wander(mut rng: Local<Rng>, mut query: Query<(&StableId, &mut Transform)>) {
+    ///     let entities = rng.iter_deterministic(query.iter_mut(), |(id, _)| **id);
+    ///
+    ///     for ((_, mut transform), mut entity_rng) in entities {
+    ///         transform.translation.x += entity_rng.gen_range(-1.0..1.0);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Sort by [`StableId`] where possible, or by `Entity` as a fallback,
+    /// which is only stable if entities are spawned in the same order every
+    /// time. Keys should be unique, as items with the same key get the same
+    /// rng.
+    ///
+    /// Exactly one value is drawn from this rng per call, no matter how many
+    /// items there are, so the next call hands out new values.
+    fn iter_deterministic<I, K, F>(
+        &mut self,
+        items: I,
+        mut key: F,
+    ) -> vec::IntoIter<(I::Item, CounterRng)>
+    where
+        I: IntoIterator,
+        K: SortKey,
+        F: FnMut(&I::Item) -> K,
+    {
+        let round = self.next_u64();
+
+        let mut items: Vec<_> = items.into_iter().map(|item| (key(&item), item)).collect();
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        items
+            .into_iter()
+            .map(|(key, item)| (item, CounterRng::new(round, key.stable_key(), 0)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<R: RngCore + ?Sized> RngIterExt for R {}
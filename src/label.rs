@@ -0,0 +1,26 @@
+/// A label identifying the stream of a system.
+///
+/// Implement this for a marker type to give a system's rng a stream that
+/// depends on the label only, rather than on when (or whether) other systems
+/// are registered:
+///
+/// ```rust,ignore
+/// struct Loot;
+///
+/// impl RngLabel for Loot {
+///     fn label() -> &'static str {
+///         "loot"
+///     }
+/// }
+/// ```
+///
+/// The default label is the type name of the marker. Type names are not
+/// guaranteed to be stable between compiler versions, or when the type is
+/// moved to a different module, so override `label` for any stream whose
+/// output must stay the same across releases of your game.
+pub trait RngLabel: Send + Sync + 'static {
+    /// The label of the stream.
+    fn label() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
@@ -1,7 +1,13 @@
-use rand::SeedableRng;
-use rand_seeder::Seeder;
+use backend::Generator;
+use rand::{RngCore, SeedableRng};
 use rand_xoshiro::Xoshiro256StarStar;
-use std::ops::{Deref, DerefMut};
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(all(feature = "bevy-nightly", not(feature = "bevy-stable")))]
 use bevy_nightly as bevy;
@@ -10,8 +16,145 @@ use bevy_stable as bevy;
 
 use bevy::prelude::*;
 
+mod animation;
+mod audio;
+mod backend;
+mod biome;
+mod checkpoint;
+mod chunk;
+#[cfg(feature = "commit")]
+mod commit;
+mod component;
+#[cfg(feature = "config")]
+mod config;
+pub mod conformance;
+mod cooldown;
+mod cost;
+mod counter;
+mod ctx;
+mod divergence;
+mod dungeon;
+mod ease;
+mod encounter;
+mod fixed;
+mod flags;
+mod float;
+mod frame;
+mod graph;
+mod hash;
+mod history;
+mod index;
+mod iter;
+mod label;
+mod lockstep;
+mod macros;
+mod marker;
+mod mutator;
+mod name;
+mod npc;
+mod params;
+mod path;
+mod pity;
+mod placement;
+mod playlist;
+mod prd;
+mod quota;
+mod random_event;
+mod rarity;
+mod replay;
+mod resolver;
+mod roll;
+mod scatter;
+mod shape;
+mod share;
+mod slug;
+mod softmax;
+mod spawner;
+mod stable_id;
+mod stream;
+mod sync;
+mod table;
+mod tick;
+mod transform;
+mod weather;
+mod wfc;
 
+pub use animation::{AnimationVariantPlugin, AnimationVariants, SelectedAnimation};
+pub use audio::{
+    PlaySample, PlayedSample, RandomPitch, RandomSample, RandomSamplePlugin, RngAudioExt,
+    SamplePlayed, SoundVariation,
+};
+pub use backend::Backend;
+pub use biome::{BiomeMap, BiomeRule, Climate, NoiseChannel};
+pub use checkpoint::{
+    load_checkpoint, save_checkpoint, CheckpointError, CheckpointInterval, CheckpointPlugin,
+    RngCheckpoint, RngPosition, CHECKPOINT_MAGIC, CHECKPOINT_VERSION,
+};
+pub use chunk::{ChunkRngs, DEFAULT_CHUNK_CAPACITY};
+#[cfg(feature = "commit")]
+pub use commit::{Commitment, CommittedSeed, Reveal, RevealError};
+pub use component::{NeedsRng, RngComponent, RngComponentPlugin};
+#[cfg(feature = "config")]
+pub use config::{
+    RandomConfig, RandomConfigLoader, RandomConfigPlugin, RandomValue, RANDOM_CONFIG_EXTENSION,
+};
+pub use cooldown::{RngVaryExt, Variation, VariedCooldown};
+pub use cost::{edge_cost_key, tile_cost_key};
+pub use counter::CounterRng;
+pub use ctx::{GlobalRng, RngCtx, RngCtxState, GLOBAL_STREAM};
+pub use divergence::{Divergence, DivergenceDetector, DivergencePlugin, TickChecksum};
+pub use dungeon::{Corridor, Dungeon, DungeonConfig, Room};
+pub use ease::{Ease, Lerp, RngEaseExt};
+pub use encounter::{
+    EncounterPlugin, EncounterScheduler, EncounterTriggered, EncounterZone, ENCOUNTER_STREAM,
+};
+pub use fixed::{RngFixedExt, FIXED_FRACTION_BITS, FIXED_ONE};
+pub use flags::{Bitflags, RngFlagsExt};
+pub use float::RngFloatExt;
+pub use frame::FrameRng;
+pub use graph::Graph;
+pub use hash::StableHasher;
+pub use history::{RollHistory, RollHistoryPlugin, RollRecord, DEFAULT_HISTORY_CAPACITY};
+pub use index::RngIndexExt;
+pub use iter::{RngIterExt, SortKey};
+pub use label::RngLabel;
+pub use lockstep::LockstepRng;
+pub use macros::ConstSeed;
+pub use marker::{Marked, RngMarker};
+pub use mutator::Mutator;
+pub use name::NameGenerator;
+pub use npc::{NpcGenerator, NpcProfile};
+pub use params::{AxisParams, Curve, RandomParams, RngParamsExt};
+pub use path::{BezierArc, CatmullRom, RngPathExt};
+pub use pity::{Pity, PityState, PityTable};
+pub use placement::RandomTransform;
+pub use playlist::ShuffledPlaylist;
+pub use prd::PrdChance;
+pub use quota::QuotaChance;
 pub use rand::Rng as _;
+pub use random_event::{
+    per_minute, per_second, RandomEvent, RandomEvents, RandomEventsAppExt, Rate,
+};
+pub use rarity::{RarityTable, RarityTier, RarityTiers, RngRarityExt};
+pub use replay::{
+    load_replay, save_replay, Replay, ReplayError, StreamLog, REPLAY_MAGIC, REPLAY_VERSION,
+};
+pub use resolver::{OutcomeResolver, Resolution};
+pub use roll::{RngRollExt, Roll, RollResult, RollSet};
+pub use scatter::{DensityFn, DensityMap, Scatter, ScatteredProp};
+pub use shape::Shape;
+pub use share::{ShareCode, ShareCodeError, SHARE_CODE_VERSION};
+pub use slug::{RngSlugExt, SLUG_LEN};
+pub use softmax::RngSoftmaxExt;
+pub use spawner::{RandomizedSpawner, SpawnFn, SpawnTarget};
+pub use stable_id::{StableId, StableIdAllocator};
+pub use stream::{RngStreams, StreamRng};
+pub use sync::{SeedChanged, SeedSync};
+pub use table::{WeightModifier, WeightedTable};
+pub use tick::{advance_tick_rng, TickRng};
+pub use transform::RngTransformExt;
+pub use weather::{WeatherChanged, WeatherMachine, WeatherPlugin, WEATHER_STREAM};
+pub use wfc::{Contradiction, Direction, TileGrid, TileRules, MAX_TILES};
 
 /// `RngPlugin` allows you to inject a (optionally seeded) random number
 /// generator into your systems.
@@ -21,96 +164,831 @@ pub use rand::Rng as _;
 ///
 /// Using `Local<T>` ensures you get a unique copy of the rng for each
 /// individual system, which is important, because systems have no deterministic
-/// order, which will result in non-deterministic rng results.
+/// order, which will result in non-deterministic rng results. Use
+/// `Local<StreamRng<L>>` to give each system its own stream, keyed by a
+/// label, or [`RngCtx`] to reach the local, global and stream rngs through
+/// one parameter.
 ///
 /// You are still responsible for deterministically generating random numbers
-/// _inside_ an individual system, which (currently) means you can't generate
-/// random numbers when iterating over entities, as entity iteration also isn't
-/// ordered currently.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct RngPlugin {
+/// _inside_ an individual system, which (currently) means you can't draw
+/// random numbers from the rng when iterating over entities, as entity
+/// iteration also isn't ordered currently. Use [`Rng::at`] to get values that
+/// only depend on a stable key instead, or
+/// [`RngIterExt::iter_deterministic`] to iterate in a stable order, with an
+/// rng for each entity.
+///
+/// Create the plugin from a seed (`RngPlugin::from(42)`), or use
+/// [`RngPlugin::builder`] to configure it further.
+///
+/// To run several independent instances side by side, e.g. one for world
+/// generation and one for cosmetic effects, distinguish them by a marker type,
+/// see [`RngMarker`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RngPlugin<M = ()> {
     seed: Option<Seed>,
+    backend: Backend,
+    streams: Vec<String>,
+    log_seed: bool,
+    marker: PhantomData<fn() -> M>,
 }
 
-impl From<String> for RngPlugin {
-    fn from(seed: String) -> Self {
+impl<M> Default for RngPlugin<M> {
+    fn default() -> Self {
         Self {
-            seed: Some(Seed::String(seed)),
+            seed: None,
+            backend: Backend::default(),
+            streams: Vec::new(),
+            log_seed: false,
+            marker: PhantomData,
         }
     }
 }
 
-impl From<&str> for RngPlugin {
-    fn from(seed: &str) -> Self {
+impl RngPlugin {
+    /// Create the plugin without a seed, see [`RngPluginBuilder::seed`].
+    ///
+    /// This is the same as `Default::default`, which needs the marker type
+    /// spelled out.
+    #[allow(clippy::should_implement_trait)]
+    pub fn default() -> Self {
+        Default::default()
+    }
+
+    /// Create the plugin with the given seed.
+    ///
+    /// This is the same as `From::from`, which needs the marker type spelled
+    /// out.
+    pub fn from(seed: impl Into<Seed>) -> Self {
+        From::from(seed)
+    }
+
+    /// Start configuring the plugin.
+    ///
+    /// ```rust,ignore
+    /// app.add_plugin(
+    ///     RngPlugin::builder()
+    ///         .seed("abc")
+    ///         .backend(Backend::Pcg64)
+    ///         .streams(["loot", "ai"])
+    ///         .log_seed(true)
+    ///         .build(),
+    /// );
+    /// ```
+    pub fn builder() -> RngPluginBuilder {
+        RngPluginBuilder::default()
+    }
+
+    /// Create the plugin with a seed derived from `value`, see
+    /// [`Seed::from_hash`].
+    pub fn from_hashable<T: Hash + ?Sized>(value: &T) -> Self {
+        Self::from(Seed::from_hash(value))
+    }
+
+    /// Create the plugin with a seed taken from the wall clock, see
+    /// [`RngPluginBuilder::seed_from_time`].
+    pub fn seed_from_time() -> Self {
+        Self::builder().seed_from_time().build()
+    }
+}
+
+impl<M, T: Into<Seed>> From<T> for RngPlugin<M> {
+    fn from(seed: T) -> Self {
+        RngPluginBuilder::default().seed(seed).build()
+    }
+}
+
+/// Configures an [`RngPlugin`], see [`RngPlugin::builder`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RngPluginBuilder<M = ()> {
+    plugin: RngPlugin<M>,
+}
+
+impl<M> Default for RngPluginBuilder<M> {
+    fn default() -> Self {
         Self {
-            seed: Some(Seed::String(seed.to_owned())),
+            plugin: Default::default(),
         }
     }
 }
 
-impl From<u64> for RngPlugin {
-    fn from(seed: u64) -> Self {
-        Self {
-            seed: Some(Seed::Number(seed)),
+impl<M> RngPluginBuilder<M> {
+    /// Configure an instance distinguished by the marker type `N` instead,
+    /// keeping the settings so far, see [`RngMarker`].
+    pub fn marker<N: RngMarker>(self) -> RngPluginBuilder<N> {
+        let RngPlugin {
+            seed,
+            backend,
+            streams,
+            log_seed,
+            ..
+        } = self.plugin;
+
+        RngPluginBuilder {
+            plugin: RngPlugin {
+                seed,
+                backend,
+                streams,
+                log_seed,
+                marker: PhantomData,
+            },
         }
     }
+
+    /// Use the given seed. Without a seed, one is drawn from the operating
+    /// system's entropy source.
+    pub fn seed(mut self, seed: impl Into<Seed>) -> Self {
+        self.plugin.seed = Some(seed.into());
+        self
+    }
+
+    /// Use a numeric seed taken from the wall clock, see [`Seed::from_time`].
+    ///
+    /// Every run is different, but not irreproducible: the seed is logged
+    /// when the plugin is added, and available as the [`Seed`] resource, so a
+    /// run can be replayed by passing that seed instead.
+    pub fn seed_from_time(self) -> Self {
+        self.seed(Seed::from_time()).log_seed(true)
+    }
+
+    /// Use the given backend to generate values, instead of the default
+    /// [`Backend::Xoshiro256StarStar`].
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.plugin.backend = backend;
+        self
+    }
+
+    /// Declare named streams, available through the [`RngStreams`] resource.
+    pub fn streams<S: Into<String>>(mut self, streams: impl IntoIterator<Item = S>) -> Self {
+        self.plugin
+            .streams
+            .extend(streams.into_iter().map(Into::into));
+        self
+    }
+
+    /// Log the seed in use when the plugin is added, so any run can be
+    /// reproduced from its logs.
+    pub fn log_seed(mut self, log_seed: bool) -> Self {
+        self.plugin.log_seed = log_seed;
+        self
+    }
+
+    /// Returns the configured plugin.
+    pub fn build(self) -> RngPlugin<M> {
+        self.plugin
+    }
 }
 
+/// The seed a random number generator was created from.
+///
+/// When no seed is provided, one is drawn from the operating system's entropy
+/// source, so every generator can report the seed needed to reproduce it.
+///
+/// The seed in use is available as a resource once the plugin is added. Use
+/// the [`SeedSync`] resource to replace it.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Seed {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Seed {
+    /// A numeric seed.
     Number(u64),
+
+    /// A string seed, hashed into the generator state.
+    ///
+    /// The derivation is implemented in this crate and versioned, so the same
+    /// string produces the same values on every platform (including wasm),
+    /// regardless of the versions of other dependencies.
     String(String),
+
+    /// A seed of raw bytes, e.g. externally generated entropy or the hash of
+    /// a save file. Every byte contributes to the generator state.
+    Bytes(Vec<u8>),
+}
+
+impl Seed {
+    /// Derive a seed by hashing `value` with the [`StableHasher`], e.g. to
+    /// seed a world from its settings.
+    ///
+    /// ```rust,ignore
+    /// #[derive(Hash)]
+    /// struct MapSettings {
+    ///     size: u32,
+    ///     biome: String,
+    /// }
+    ///
+    /// let seed = Seed::from_hash(&settings);
+    /// ```
+    pub fn from_hash<T: Hash + ?Sized>(value: &T) -> Self {
+        Self::Number(hash::hash_one(value))
+    }
+
+    /// Returns a numeric seed taken from the wall clock, the number of
+    /// nanoseconds since the Unix epoch.
+    pub fn from_time() -> Self {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self::Number(time.as_nanos() as u64)
+    }
+
+    fn from_entropy() -> Self {
+        Self::Number(Xoshiro256StarStar::from_entropy().next_u64())
+    }
+
+    fn make_rng(&self) -> Xoshiro256StarStar {
+        match self {
+            Self::String(seed) => Xoshiro256StarStar::from_seed(hash::seed_string(seed)),
+            Self::Number(num) => Xoshiro256StarStar::seed_from_u64(*num),
+            Self::Bytes(bytes) => Xoshiro256StarStar::from_seed(hash::seed_bytes(bytes)),
+        }
+    }
+
+    /// Returns the rng for the given tick.
+    ///
+    /// Its stream is derived from the seed and the tick number only, so the
+    /// randomness of any tick can be computed directly. This is the rng held
+    /// by the [`TickRng`] resource for that tick.
+    ///
+    /// The rng uses the default [`Backend`], see [`Rng::backend`].
+    pub fn tick_rng(&self, tick: u64) -> Rng {
+        self.tick_rng_with(Backend::default(), tick)
+    }
+
+    pub(crate) fn tick_rng_with(&self, backend: Backend, tick: u64) -> Rng {
+        let key = hash::combine(hash::hash_bytes(b"tick"), tick);
+
+        Rng {
+            tick: Some(tick),
+            ..Rng::derived(backend, self.clone(), None, key)
+        }
+    }
+
+    /// A stable 64-bit key identifying this seed, used by the counter-based
+    /// generators.
+    pub(crate) fn key(&self) -> u64 {
+        match self {
+            Self::Number(num) => hash::combine(0, *num),
+            Self::String(seed) => hash::combine(1, hash::hash_bytes(seed.as_bytes())),
+            Self::Bytes(bytes) => hash::combine(2, hash::hash_bytes(bytes)),
+        }
+    }
+}
+
+/// Creates a generator whose state is derived from the key of a seed and
+/// `key`.
+fn derive(seed_key: u64, key: u64) -> Xoshiro256StarStar {
+    Xoshiro256StarStar::seed_from_u64(derive_seed(seed_key, key))
+}
+
+/// The value [`derive`] seeds its generator with.
+fn derive_seed(seed_key: u64, key: u64) -> u64 {
+    hash::combine(seed_key, key)
+}
+
+impl From<String> for Seed {
+    fn from(seed: String) -> Self {
+        Self::String(seed)
+    }
+}
+
+impl From<&str> for Seed {
+    fn from(seed: &str) -> Self {
+        Self::String(seed.to_owned())
+    }
+}
+
+impl From<u64> for Seed {
+    fn from(seed: u64) -> Self {
+        Self::Number(seed)
+    }
+}
+
+impl From<Vec<u8>> for Seed {
+    fn from(seed: Vec<u8>) -> Self {
+        Self::Bytes(seed)
+    }
+}
+
+impl From<&[u8]> for Seed {
+    fn from(seed: &[u8]) -> Self {
+        Self::Bytes(seed.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Seed {
+    fn from(seed: [u8; N]) -> Self {
+        Self::Bytes(seed.to_vec())
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(num) => write!(f, "{}", num),
+            Self::String(seed) => write!(f, "{:?}", seed),
+            Self::Bytes(bytes) => {
+                f.write_str("0x")?;
+                bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+        }
+    }
+}
+
+impl<M> RngPlugin<M> {
+    /// Returns the configured seed, or one drawn from the operating system's
+    /// entropy source, logging it if configured to.
+    fn resolve_seed(&self) -> Seed {
+        let seed = self.seed.clone().unwrap_or_else(Seed::from_entropy);
+        if self.log_seed {
+            info!("rng seed: {}", seed);
+        }
+
+        seed
+    }
 }
 
 impl Plugin for RngPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        let rng = match &self.seed {
-            Some(Seed::String(seed)) => Seeder::from(seed.as_str()).make_rng(),
-            Some(Seed::Number(num)) => Xoshiro256StarStar::seed_from_u64(*num),
-            None => Xoshiro256StarStar::from_entropy(),
-        };
+        let seed = self.resolve_seed();
 
-        app.insert_resource(RootRng { rng });
+        let root = RootRng::new(seed.clone(), self.backend);
+
+        app.insert_resource(FrameRng::new(&seed))
+            .insert_resource(TickRng::new(&seed, self.backend))
+            .insert_resource(RngStreams::new(&root, &self.streams))
+            .insert_resource(root)
+            .insert_resource(seed)
+            .init_resource::<GlobalRng>()
+            .init_resource::<SeedSync>()
+            .init_resource::<StableIdAllocator>()
+            .add_event::<SeedChanged>()
+            .add_system_to_stage(
+                CoreStage::First,
+                sync::apply_seed.system().label(RngSystem::Reseed),
+            )
+            .add_system(spawner::run_spawners.system())
+            .add_system(cooldown::tick_cooldowns.system())
+            .add_system(placement::apply_random_transforms.system())
+            .add_system_to_stage(
+                CoreStage::Last,
+                frame::advance_frame.system().label(RngSystem::Derive),
+            );
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Labels for the systems added by [`RngPlugin`], to order your own systems
+/// around the rng lifecycle.
+///
+/// ```rust,ignore
+/// app.add_system_to_stage(
+///     CoreStage::First,
+///     start_new_run.system().before(RngSystem::Reseed),
+/// )
+/// .add_system_to_stage(
+///     CoreStage::First,
+///     draw_level_layout.system().after(RngSystem::Reseed),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RngSystem {
+    /// Applies the seed scheduled through [`SeedSync`], in
+    /// `CoreStage::First`.
+    ///
+    /// Systems scheduling a new seed should run before it, for the swap to
+    /// take effect in the same frame. Systems running after it draw from the
+    /// new seed.
+    Reseed,
+
+    /// Advances the derived per-frame streams of [`FrameRng`], in
+    /// `CoreStage::Last`.
+    ///
+    /// Label your own [`advance_tick_rng`] system with it, so everything
+    /// deriving the next stream can be ordered in one place.
+    Derive,
+}
+
+impl SystemLabel for RngSystem {
+    fn dyn_clone(&self) -> Box<dyn SystemLabel> {
+        Box::new(*self)
+    }
+}
+
+/// The root rng all `Local<Rng>` instances are created from.
+///
+/// Its state is shared with those instances, so that replacing the seed also
+/// reaches the rngs that were created before the swap.
+#[derive(Debug, Clone)]
 struct RootRng {
-    rng: Xoshiro256StarStar,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    generation: AtomicU64,
+    root: RwLock<Root>,
+}
+
+/// Everything rngs are created from, computed once per seed, so that creating
+/// an rng only copies it rather than hashing the seed again.
+#[derive(Debug, Clone)]
+struct Root {
+    generation: u64,
+    backend: Backend,
+    seed: Seed,
+
+    /// The key of the seed, see [`Seed::key`].
+    key: u64,
+
+    /// The generator every `Local<Rng>` starts with.
+    local: Generator,
+}
+
+impl Root {
+    fn new(generation: u64, backend: Backend, seed: Seed) -> Self {
+        let local = Xoshiro256StarStar::from_rng(seed.make_rng()).expect("failed to create rng");
+
+        Self {
+            generation,
+            backend,
+            key: seed.key(),
+            local: Generator::new(backend, local),
+            seed,
+        }
+    }
+
+    /// Returns the rng derived from the seed and the given key, see
+    /// [`Rng::derived`].
+    fn derived(&self, stream: Option<String>, key: u64) -> Rng {
+        Rng::derived_from_key(self.backend, self.seed.clone(), self.key, stream, key)
+    }
 }
 
+impl RootRng {
+    fn new(seed: Seed, backend: Backend) -> Self {
+        let root = Root::new(0, backend, seed);
+
+        Self {
+            shared: Arc::new(Shared {
+                generation: AtomicU64::new(0),
+                root: RwLock::new(root),
+            }),
+        }
+    }
+
+    fn reseed(&self, seed: Seed) {
+        let mut root = self.shared.root.write().unwrap();
+
+        let generation = root.generation + 1;
+        #[cfg(feature = "trace")]
+        tracing::info!(seed = %seed, generation, "rng reseeded");
+
+        *root = Root::new(generation, root.backend, seed);
+
+        self.shared
+            .generation
+            .store(root.generation, Ordering::Release);
+    }
+
+    fn rng(&self) -> Rng {
+        let root = self.shared.root.read().unwrap().clone();
+
+        Rng {
+            link: Some(self.link(&root)),
+            ..Rng::from_root(root)
+        }
+    }
+
+    /// Returns the rng of the named stream, following seed swaps.
+    fn stream_rng(&self, label: &str) -> Rng {
+        let root = self.shared.root.read().unwrap().clone();
+        #[cfg(feature = "trace")]
+        tracing::debug!(
+            stream = label,
+            generation = root.generation,
+            "rng stream created"
+        );
+
+        Rng {
+            link: Some(self.link(&root)),
+            ..root.derived(Some(label.to_owned()), stream::stream_key(label))
+        }
+    }
+
+    fn link(&self, root: &Root) -> Link {
+        Link {
+            shared: self.shared.clone(),
+            generation: root.generation,
+        }
+    }
+}
+
+/// Connects an rng to the [`RootRng`] it was created from.
+#[derive(Debug, Clone)]
+struct Link {
+    shared: Arc<Shared>,
+    generation: u64,
+}
+
+impl Link {
+    fn is_stale(&self) -> bool {
+        self.shared.generation.load(Ordering::Acquire) != self.generation
+    }
+}
+
+impl PartialEq for Link {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared) && self.generation == other.generation
+    }
+}
+
+impl Eq for Link {}
+
 /// The Rng resource.
 ///
 /// This wraps a random number generator.
 ///
 /// See the `rand::Rng` trait for more details on how to generate random data.
+///
+/// Every value drawn is counted, see [`Rng::seed_info`]. The values are
+/// generated by the [`Backend`] configured on the [`RngPlugin`], so unlike
+/// earlier versions, the rng doesn't deref to the generator of a backend.
+///
+/// When the seed is replaced through [`SeedSync`], the rng starts over from
+/// the new seed before its next draw.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rng {
-    inner: Xoshiro256StarStar,
+    inner: Generator,
+    seed: Seed,
+    stream: Option<String>,
+    key: Option<u64>,
+    tick: Option<u64>,
+    draws: u64,
+    steps: u64,
+    link: Option<Link>,
 }
 
-impl Deref for Rng {
-    type Target = Xoshiro256StarStar;
+impl Rng {
+    fn derived(backend: Backend, seed: Seed, stream: Option<String>, key: u64) -> Self {
+        let seed_key = seed.key();
+        Self::derived_from_key(backend, seed, seed_key, stream, key)
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    /// Like [`Rng::derived`], with the key of the seed computed up front.
+    fn derived_from_key(
+        backend: Backend,
+        seed: Seed,
+        seed_key: u64,
+        stream: Option<String>,
+        key: u64,
+    ) -> Self {
+        Self {
+            inner: Generator::new(backend, derive(seed_key, key)),
+            seed,
+            stream,
+            key: Some(key),
+            tick: None,
+            draws: 0,
+            steps: 0,
+            link: None,
+        }
+    }
+
+    fn from_root(root: Root) -> Self {
+        Self {
+            inner: root.local,
+            seed: root.seed,
+            stream: None,
+            key: None,
+            tick: None,
+            draws: 0,
+            steps: 0,
+            link: None,
+        }
+    }
+
+    /// Start over from the root rng, if it was reseeded since this rng was
+    /// created. Derived rngs are derived again from the new seed.
+    fn sync(&mut self) {
+        let link = match &self.link {
+            Some(link) if link.is_stale() => link,
+            _ => return,
+        };
+
+        let root = link.shared.root.read().unwrap().clone();
+        let link = Link {
+            shared: link.shared.clone(),
+            generation: root.generation,
+        };
+
+        #[cfg(feature = "trace")]
+        tracing::debug!(
+            stream = ?self.stream,
+            generation = root.generation,
+            draws = self.draws,
+            "rng restarted from new seed"
+        );
+
+        let rng = match self.key {
+            Some(key) => {
+                Self::derived_from_key(self.backend(), root.seed, root.key, self.stream.take(), key)
+            }
+            None => Self {
+                stream: self.stream.take(),
+                ..Self::from_root(root)
+            },
+        };
+
+        *self = Self {
+            tick: self.tick,
+            link: Some(link),
+            ..rng
+        };
+    }
+
+    /// Returns this rng as it will be at its next draw.
+    fn synced(&self) -> Cow<'_, Self> {
+        match &self.link {
+            Some(link) if link.is_stale() => {
+                let mut rng = self.clone();
+                rng.sync();
+                Cow::Owned(rng)
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// Returns the seed this rng originates from, the stream it belongs to and
+    /// the number of values drawn from it so far.
+    pub fn seed_info(&self) -> SeedInfo {
+        let rng = self.synced();
+
+        SeedInfo {
+            seed: rng.seed.clone(),
+            stream: rng.stream.clone(),
+            draws: rng.draws,
+        }
+    }
+
+    /// Create an independent rng from this one.
+    ///
+    /// The fork starts a new sequence derived from the next values of this
+    /// rng, so forking is deterministic and counts as a draw. It belongs to
+    /// the same stream, but doesn't follow a seed swap through [`SeedSync`].
+    pub fn fork(&mut self) -> Rng {
+        let state = Xoshiro256StarStar::from_rng(&mut *self).expect("failed to fork rng");
+        let synced = self.synced();
+        #[cfg(feature = "trace")]
+        tracing::debug!(stream = ?synced.stream, draws = synced.draws, "rng forked");
+
+        Rng {
+            inner: Generator::new(synced.backend(), state),
+            seed: synced.seed.clone(),
+            stream: synced.stream.clone(),
+            key: None,
+            tick: synced.tick,
+            draws: 0,
+            steps: 0,
+            link: None,
+        }
+    }
+
+    /// Run the closure on a copy of this rng, leaving this rng untouched.
+    ///
+    /// The copy draws exactly the values this rng would draw next, so it can
+    /// preview outcomes, e.g. to show the possible rolls in the UI, without
+    /// advancing the stream or changing the draw count:
+    ///
+    /// ```rust,ignore
+    /// let preview = rng.frozen_scope(|rng| loot.pick(rng).cloned());
+    /// let dropped = loot.pick(&mut rng).cloned();
+    /// assert_eq!(preview, dropped);
+    /// ```
+    pub fn frozen_scope<T>(&self, scope: impl FnOnce(&mut Rng) -> T) -> T {
+        let mut frozen = self.clone();
+        scope(&mut frozen)
+    }
+
+    /// Returns the backend generating the values of this rng.
+    pub fn backend(&self) -> Backend {
+        self.inner.backend()
+    }
+
+    /// Returns a checksum of the rng's current state and draw count.
+    ///
+    /// Two rngs with the same checksum are, for all practical purposes, in the
+    /// same state, which makes it a cheap way to compare rngs across app
+    /// instances. See [`DivergenceDetector`].
+    pub fn checksum(&self) -> u64 {
+        let rng = self.synced();
+
+        hash::combine(rng.inner.clone().next_u64(), rng.draws)
+    }
+
+    /// Returns a stateless generator for the `index`-th value of the `key`
+    /// stream.
+    ///
+    /// The result is computed from `(seed, key, index)` only: it doesn't
+    /// advance this rng, isn't counted as a draw, and is identical for every
+    /// rng sharing the same seed. This makes it safe to use while iterating
+    /// over entities, as long as `key` is stable for each entity.
+    ///
+    /// See [`CounterRng`] for details.
+    pub fn at(&self, key: u64, index: u64) -> CounterRng {
+        CounterRng::new(self.synced().seed.key(), key, index)
+    }
+
+    /// Create an rng from a seed drawn from the operating system's entropy
+    /// source, for apps without the plugin.
+    fn from_entropy() -> Self {
+        let seed = Seed::from_entropy();
+
+        Self::from_root(Root::new(0, Backend::default(), seed))
+    }
+
+    /// Count a draw advancing the generator by `steps` 64-bit outputs.
+    fn count_draw(&mut self, steps: u64) {
+        self.sync();
+        self.draws += 1;
+        self.steps += steps;
+
+        #[cfg(feature = "trace")]
+        if self.draws % TRACE_DRAW_SAMPLE == 1 {
+            tracing::trace!(
+                stream = ?self.stream,
+                tick = ?self.tick,
+                draws = self.draws,
+                "rng draw"
+            );
+        }
     }
 }
 
-impl DerefMut for Rng {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+/// With the `trace` feature, every draw of this many is traced, starting with
+/// the first one, to keep the overhead low.
+#[cfg(feature = "trace")]
+const TRACE_DRAW_SAMPLE: u64 = 256;
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.count_draw(1);
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.count_draw(1);
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.count_draw(steps_for(dest.len()));
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.count_draw(steps_for(dest.len()));
+        self.inner.try_fill_bytes(dest)
     }
 }
 
+/// The number of steps both backends take to fill `len` bytes: one per 8
+/// bytes, and one for the remainder.
+fn steps_for(len: usize) -> u64 {
+    ((len + 7) / 8) as u64
+}
+
 impl FromWorld for Rng {
     fn from_world(world: &mut World) -> Self {
-        let inner = match world.get_resource::<RootRng>() {
-            Some(rng) => Xoshiro256StarStar::from_rng(rng.rng.clone())
-                .expect("failed to create rng"),
-            None => Xoshiro256StarStar::from_entropy(),
-        };
+        match world.get_resource::<RootRng>() {
+            Some(root) => root.rng(),
+            None => Self::from_entropy(),
+        }
+    }
+}
+
+/// Describes where the values of an [`Rng`] come from.
+///
+/// The `Display` implementation renders as `seed 42, stream loot, draw #4812`,
+/// which is handy to include in logs and bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeedInfo {
+    /// The seed the rng originates from.
+    pub seed: Seed,
+
+    /// The named stream the rng belongs to, if any.
+    pub stream: Option<String>,
+
+    /// The number of values drawn so far.
+    pub draws: u64,
+}
+
+impl fmt::Display for SeedInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}", self.seed)?;
+
+        if let Some(stream) = &self.stream {
+            write!(f, ", stream {}", stream)?;
+        }
 
-        Self { inner }
+        write!(f, ", draw #{}", self.draws)
     }
 }
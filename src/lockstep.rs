@@ -0,0 +1,84 @@
+use crate::{hash, Backend, Rng, RngLabel, Seed, TickRng};
+use std::marker::PhantomData;
+
+/// A per-system rng for deterministic lockstep simulations.
+///
+/// Its stream is re-derived from `(seed, tick, label)` on every fixed tick, so
+/// nothing carries over between ticks: a single missed or extra draw on one
+/// client only affects that tick, instead of cascading into a permanent
+/// desync.
+///
+/// Use it as a `Local`, together with the [`TickRng`] resource that tracks the
+/// current tick:
+///
+/// ```rust,ignore
+/// struct Ai;
+///
+/// impl RngLabel for Ai {
+///     fn label() -> &'static str {
+///         "ai"
+///     }
+/// }
+///
+/// fn ai(mut rng: Local<LockstepRng<Ai>>, tick_rng: Res<TickRng>) {
+///     let rng = rng.for_tick(&tick_rng);
+///     let roll: f32 = rng.gen();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockstepRng<L: RngLabel> {
+    current: Option<(u64, Rng)>,
+    label: PhantomData<fn() -> L>,
+}
+
+impl<L: RngLabel> Default for LockstepRng<L> {
+    fn default() -> Self {
+        Self {
+            current: None,
+            label: PhantomData,
+        }
+    }
+}
+
+impl<L: RngLabel> LockstepRng<L> {
+    /// Returns the rng for the current tick, deriving a fresh stream when the
+    /// tick (or the seed) changed since the last call.
+    pub fn for_tick(&mut self, tick_rng: &TickRng) -> &mut Rng {
+        let tick = tick_rng.tick();
+        let seed = &tick_rng.seed;
+
+        let stale = match &self.current {
+            Some((current, rng)) => *current != tick || rng.seed != *seed,
+            None => true,
+        };
+
+        if stale {
+            let rng = seed.lockstep_rng_with(tick_rng.backend(), tick, L::label());
+            self.current = Some((tick, rng));
+        }
+
+        let (_, rng) = self.current.as_mut().expect("rng was just derived");
+        rng
+    }
+}
+
+impl Seed {
+    /// Returns the rng for the system with the given label, at the given tick.
+    ///
+    /// This is the rng a [`LockstepRng`] holds for that tick, which lets you
+    /// compute it directly, e.g. when validating a replay.
+    ///
+    /// The rng uses the default [`Backend`], see [`Rng::backend`].
+    pub fn lockstep_rng(&self, tick: u64, label: &str) -> Rng {
+        self.lockstep_rng_with(Backend::default(), tick, label)
+    }
+
+    pub(crate) fn lockstep_rng_with(&self, backend: Backend, tick: u64, label: &str) -> Rng {
+        let key = hash::combine(
+            hash::combine(hash::hash_bytes(b"lockstep"), tick),
+            hash::hash_bytes(label.as_bytes()),
+        );
+
+        Rng::derived(backend, self.clone(), Some(label.to_owned()), key)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{hash, Seed};
+
+/// Hash a seed string at compile time.
+///
+/// `seed!("my-game-v1")` is a [`ConstSeed`], the string seed
+/// `Seed::from("my-game-v1")` in a form that can initialize constants. The
+/// generator state the string seed derives is computed by the compiler, and
+/// converting it into a [`Seed`] gives the string seed itself, so replacing
+/// a runtime seed with the macro keeps every generated value the same:
+///
+/// ```rust,ignore
+/// const DEFAULT_SEED: ConstSeed = seed!("my-game-v1");
+///
+/// app.add_plugin(RngPlugin::from(DEFAULT_SEED));
+/// ```
+#[macro_export]
+macro_rules! seed {
+    ($seed:expr) => {{
+        const SEED: $crate::ConstSeed = $crate::ConstSeed::new($seed);
+        SEED
+    }};
+}
+
+/// A string seed hashed at compile time, see [`seed!`].
+///
+/// It converts into the string seed [`Seed::String`], so the rngs created
+/// from it are the same as the ones created from the string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstSeed {
+    seed: &'static str,
+    state: [u8; 32],
+}
+
+impl ConstSeed {
+    /// Hash the seed string. Prefer the [`seed!`] macro, which guarantees
+    /// that this happens at compile time.
+    pub const fn new(seed: &'static str) -> Self {
+        Self {
+            seed,
+            state: hash::seed_string(seed),
+        }
+    }
+
+    /// The seed string.
+    pub const fn as_str(&self) -> &'static str {
+        self.seed
+    }
+
+    /// The 256-bit state the generator of the string seed starts from, the
+    /// same on every platform.
+    pub const fn state(&self) -> [u8; 32] {
+        self.state
+    }
+}
+
+impl From<ConstSeed> for Seed {
+    fn from(seed: ConstSeed) -> Self {
+        Self::String(seed.seed.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Backend, RootRng};
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    const SEED: ConstSeed = seed!("my-game-v1");
+
+    #[test]
+    fn is_the_string_seed() {
+        assert_eq!(SEED.as_str(), "my-game-v1");
+        assert_eq!(Seed::from(SEED), Seed::from("my-game-v1"));
+        assert_eq!(
+            Xoshiro256StarStar::from_seed(SEED.state()),
+            Seed::from("my-game-v1").make_rng()
+        );
+    }
+
+    #[test]
+    fn draws_like_the_string_seed() {
+        let outputs = |seed: Seed| {
+            let root = RootRng::new(seed.clone(), Backend::default());
+            let mut rngs = [
+                root.rng(),
+                root.stream_rng("loot"),
+                seed.stream_rng("loot"),
+                seed.tick_rng(3),
+            ];
+
+            rngs.iter_mut()
+                .map(|rng| rng.next_u64())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(outputs(SEED.into()), outputs(Seed::from("my-game-v1")));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::bevy::prelude::*;
+use crate::{FrameRng, Rng, RngPlugin, RngStreams, RngSystem, RootRng, TickRng};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A marker type distinguishing an independent [`RngPlugin`] instance.
+///
+/// A single `RngPlugin` owns the [`Seed`](crate::Seed) resource and the
+/// rngs derived from it, so adding a second one would replace them. An
+/// `RngPlugin<M>` with a marker instead adds its own, independently seeded
+/// copy of each resource, wrapped in [`Marked<M, _>`](Marked):
+///
+/// ```rust,ignore
+/// struct WorldGen;
+///
+/// impl RngMarker for WorldGen {}
+///
+/// app.add_plugin(RngPlugin::from("shared world seed"))
+///     .add_plugin(RngPlugin::<WorldGen>::from(1));
+///
+/// fn generate_world(
+///     mut rng: Local<Marked<WorldGen, Rng>>,
+///     seed: Res<Marked<WorldGen, Seed>>,
+/// ) {
+///     let height: f32 = rng.gen();
+/// }
+/// ```
+///
+/// A marked instance inserts the `Seed`, [`FrameRng`], [`TickRng`] and
+/// [`RngStreams`] resources, and `Local<Marked<M, Rng>>` gives each system
+/// its own rng, like `Local<Rng>` does for the unmarked instance. Its frame
+/// values advance in the [`RngSystem::Derive`] system set, its ticks must be
+/// advanced by you. The seed of a marked instance can't be replaced through
+/// [`SeedSync`](crate::SeedSync), which only applies to the unmarked instance.
+pub trait RngMarker: Send + Sync + 'static {}
+
+/// A resource (or per-system rng) belonging to the [`RngPlugin`] instance
+/// marked with `M`, see [`RngMarker`].
+pub struct Marked<M, T> {
+    value: T,
+    marker: PhantomData<fn() -> M>,
+}
+
+impl<M, T> Marked<M, T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<M, T> Deref for Marked<M, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<M, T> DerefMut for Marked<M, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<M, T: fmt::Debug> fmt::Debug for Marked<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Marked").field(&self.value).finish()
+    }
+}
+
+impl<M, T: Clone> Clone for Marked<M, T> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<M, T: PartialEq> PartialEq for Marked<M, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<M, T: Eq> Eq for Marked<M, T> {}
+
+impl<M: RngMarker> FromWorld for Marked<M, Rng> {
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world.get_resource::<Marked<M, RootRng>>() {
+            Some(root) => root.rng(),
+            None => Rng::from_entropy(),
+        };
+
+        Self::new(rng)
+    }
+}
+
+impl<M: RngMarker> Plugin for RngPlugin<M> {
+    fn build(&self, app: &mut AppBuilder) {
+        let seed = self.resolve_seed();
+        let root = RootRng::new(seed.clone(), self.backend);
+
+        app.insert_resource(Marked::<M, _>::new(FrameRng::new(&seed)))
+            .insert_resource(Marked::<M, _>::new(TickRng::new(&seed, self.backend)))
+            .insert_resource(Marked::<M, _>::new(RngStreams::new(&root, &self.streams)))
+            .insert_resource(Marked::<M, _>::new(root))
+            .insert_resource(Marked::<M, _>::new(seed))
+            .add_system_to_stage(
+                CoreStage::Last,
+                advance_frame::<M>.system().label(RngSystem::Derive),
+            );
+    }
+}
+
+fn advance_frame<M: RngMarker>(mut frame_rng: ResMut<Marked<M, FrameRng>>) {
+    frame_rng.advance();
+}
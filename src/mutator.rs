@@ -0,0 +1,184 @@
+use crate::{index, RngFloatExt, WeightedTable};
+use rand::Rng;
+
+/// Applies random mutations to procedural content, e.g. to evolve the enemy
+/// waves of a game between runs.
+///
+/// Each operation is applied with the mutation rate of the mutator, one by
+/// default, and returns whether it changed anything:
+///
+/// ```rust,ignore
+/// let mut mutator = Mutator::new(&mut rng).with_rate(0.3);
+///
+/// for wave in &mut waves {
+///     mutator.perturb(&mut wave.delay, 0.2);
+///     mutator.swap(&mut wave.enemies);
+///     mutator.resample(&mut wave.boss, &bosses);
+/// }
+/// ```
+///
+/// The built-in operations draw the same values whether they are applied or
+/// not, so a change of rate doesn't shift the values of later operations.
+#[derive(Debug)]
+pub struct Mutator<'a, R: ?Sized> {
+    rng: &'a mut R,
+    rate: f32,
+    applied: u32,
+}
+
+impl<'a, R: Rng + ?Sized> Mutator<'a, R> {
+    /// Create a mutator applying every operation, drawing from `rng`.
+    pub fn new(rng: &'a mut R) -> Self {
+        Self {
+            rng,
+            rate: 1.0,
+            applied: 0,
+        }
+    }
+
+    /// Apply each operation with the given probability.
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// The probability each operation is applied with.
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// The number of operations applied so far.
+    pub fn applied(&self) -> u32 {
+        self.applied
+    }
+
+    /// Scale the value by up to `±fraction`, e.g. `0.1` for ±10%. Draws two
+    /// values.
+    pub fn perturb(&mut self, value: &mut f32, fraction: f32) -> bool {
+        let apply = self.roll();
+        let factor = 1.0 + fraction * self.rng.snorm();
+
+        apply && self.apply(|| *value *= factor)
+    }
+
+    /// Offset the integer by up to `±amount`, saturating at the bounds of
+    /// `i32`. Draws two values.
+    pub fn nudge(&mut self, value: &mut i32, amount: u32) -> bool {
+        let apply = self.roll();
+        let span = u64::from(amount) * 2 + 1;
+        let offset = index::scale(self.rng.next_u64(), span) as i64 - i64::from(amount);
+        let nudged = (i64::from(*value) + offset).clamp(i64::from(i32::MIN), i64::from(i32::MAX));
+
+        apply && self.apply(|| *value = nudged as i32)
+    }
+
+    /// Swap two different items. Draws three values, and does nothing with
+    /// less than two items.
+    pub fn swap<T>(&mut self, items: &mut [T]) -> bool {
+        let apply = self.roll();
+        let len = items.len() as u64;
+        let first = index::scale(self.rng.next_u64(), len) as usize;
+        let second = index::scale(self.rng.next_u64(), len.saturating_sub(1)) as usize;
+
+        if !apply || len < 2 {
+            return false;
+        }
+
+        self.apply(|| {
+            // Skip over the first item, keeping the others equally likely.
+            let second = if second >= first { second + 1 } else { second };
+            items.swap(first, second);
+        })
+    }
+
+    /// Replace the value with one picked from the table. Draws one value,
+    /// then whatever [`WeightedTable::pick`] draws, and does nothing if the
+    /// table has no entry with a positive weight.
+    ///
+    /// The same value may be picked again, which counts as applied.
+    pub fn resample<T: Clone>(&mut self, value: &mut T, table: &WeightedTable<T>) -> bool {
+        let apply = self.roll();
+
+        match table.pick(self.rng) {
+            Some(picked) if apply => self.apply(|| *value = picked.clone()),
+            _ => false,
+        }
+    }
+
+    /// Apply a custom mutation, drawing from the rng of the mutator. Draws one
+    /// value, and whatever the mutation draws if it is applied.
+    pub fn mutate(&mut self, mutation: impl FnOnce(&mut R)) -> bool {
+        if !self.roll() {
+            return false;
+        }
+
+        mutation(self.rng);
+        self.applied += 1;
+        true
+    }
+
+    fn roll(&mut self) -> bool {
+        self.rng.f32_01() < self.rate
+    }
+
+    fn apply(&mut self, mutation: impl FnOnce()) -> bool {
+        mutation();
+        self.applied += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn mutate(rng: &mut Xoshiro256StarStar, rate: f32) -> (f32, i32, Vec<u32>) {
+        let (mut delay, mut count, mut items) = (1.0, 5, vec![1, 2, 3, 4]);
+
+        let mut mutator = Mutator::new(rng).with_rate(rate);
+        mutator.perturb(&mut delay, 0.5);
+        mutator.nudge(&mut count, 3);
+        mutator.swap(&mut items);
+        mutator.swap(&mut [0]);
+
+        (delay, count, items)
+    }
+
+    #[test]
+    fn draws_the_same_values_at_any_rate() {
+        let mut expected = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..2 + 2 + 3 + 3 {
+            expected.next_u64();
+        }
+
+        for rate in [0.0, 0.5, 1.0].iter() {
+            let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+            mutate(&mut rng, *rate);
+            assert_eq!(rng, expected);
+        }
+
+        let unchanged = mutate(&mut Xoshiro256StarStar::seed_from_u64(1), 0.0);
+        assert_eq!(unchanged, (1.0, 5, vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn operations_stay_in_bounds() {
+        for seed in 0..200 {
+            let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+            let (delay, count, mut items) = mutate(&mut rng, 1.0);
+
+            assert!((0.5..=1.5).contains(&delay));
+            assert!((2..=8).contains(&count));
+            assert_ne!(items, [1, 2, 3, 4]);
+            items.sort_unstable();
+            assert_eq!(items, [1, 2, 3, 4]);
+        }
+
+        // Saturates instead of overflowing.
+        let mut value = i32::MAX;
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        Mutator::new(&mut rng).nudge(&mut value, u32::MAX);
+    }
+}
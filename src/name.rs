@@ -0,0 +1,112 @@
+use crate::index::below;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// Generates names by joining random syllables, e.g. `"Kavelor"`.
+///
+/// ```rust,ignore
+/// let names = NameGenerator::new(vec!["ka", "ve", "lor", "mi", "dra", "th"]);
+/// let name = names.generate(&mut rng);
+/// ```
+///
+/// The first letter of the name is capitalized. The same syllable is never
+/// used twice in a row, as long as there are at least two.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NameGenerator {
+    /// The syllables names are made of.
+    pub syllables: Vec<String>,
+
+    /// The number of syllables of each name, two to three by default.
+    pub length: RangeInclusive<u32>,
+}
+
+impl NameGenerator {
+    /// Create a generator joining two to three of the syllables.
+    pub fn new<S: Into<String>>(syllables: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            syllables: syllables.into_iter().map(Into::into).collect(),
+            length: 2..=3,
+        }
+    }
+
+    /// Join the given number of syllables.
+    pub fn with_length(mut self, length: RangeInclusive<u32>) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Generate a name, or an empty string without syllables.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        if self.syllables.is_empty() {
+            return String::new();
+        }
+
+        let (min, max) = (*self.length.start(), *self.length.end());
+        let length = if min < max {
+            rng.gen_range(min..=max)
+        } else {
+            min
+        };
+
+        // Indices are drawn as 64-bit integers, so names don't depend on the
+        // pointer width of the platform.
+        let count = self.syllables.len() as u64;
+        let mut name = String::new();
+        let mut last = None;
+        for _ in 0..length {
+            let mut index = below(rng, count) as usize;
+            if last == Some(index) && count > 1 {
+                // Skip over the last syllable, keeping the others equally likely.
+                let skip = 1 + below(rng, count - 1) as usize;
+                index = (index + skip) % self.syllables.len();
+            }
+
+            name.push_str(&self.syllables[index]);
+            last = Some(index);
+        }
+
+        capitalize(&name)
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn never_repeats_a_syllable() {
+        let names = NameGenerator::new(vec!["ka", "ve"]).with_length(6..=6);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        for _ in 0..100 {
+            let name = names.generate(&mut rng);
+            assert!(name == "Kavekavekave" || name == "Vekavekaveka", "{}", name);
+        }
+    }
+
+    #[test]
+    fn only_depends_on_the_rng() {
+        let names = NameGenerator::new(vec!["ka", "ve", "lor", "mi", "dra", "th"]);
+        let mut a = Xoshiro256StarStar::seed_from_u64(2);
+        let mut b = Xoshiro256StarStar::seed_from_u64(2);
+        for _ in 0..100 {
+            let name = names.generate(&mut a);
+            assert!(name.chars().next().unwrap().is_uppercase());
+            assert_eq!(name, names.generate(&mut b));
+        }
+
+        assert_eq!(
+            NameGenerator::new(Vec::<String>::new()).generate(&mut a),
+            ""
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{hash, Curve, NameGenerator, RandomParams, Rng, RngParamsExt, StableId, WeightedTable};
+use std::ops::Range;
+
+/// A profile rolled by an [`NpcGenerator`].
+///
+/// This is plain data; map it to the components of your game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NpcProfile<T> {
+    /// The name of the NPC.
+    pub name: String,
+
+    /// The stats of the NPC, in the order they were added to the generator.
+    pub stats: Vec<(String, f32)>,
+
+    /// The traits of the NPC, one per trait table of the generator that has
+    /// an entry with a positive weight.
+    pub traits: Vec<T>,
+}
+
+impl<T> NpcProfile<T> {
+    /// Returns the stat with the given name.
+    pub fn stat(&self, name: &str) -> Option<f32> {
+        self.stats
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Rolls NPC profiles: a name, stats and traits.
+///
+/// ```rust,ignore
+/// let villagers = NpcGenerator::new(NameGenerator::new(vec!["al", "ba", "dor", "en", "ric"]))
+///     .with_stat("strength", 3.0..18.0)
+///     .with_stat("charisma", 3.0..18.0)
+///     .with_traits(vec![(Trait::Brave, 1.0), (Trait::Cowardly, 1.0), (Trait::Calm, 3.0)])
+///     .with_traits(vec![(Job::Farmer, 6.0), (Job::Smith, 1.0)]);
+///
+/// let profile = villagers.generate(&rng, StableId::from_name("villager 7"));
+/// ```
+///
+/// Stats are drawn from bell curves by default, so most NPCs are average and
+/// few are exceptional. Each trait table adds one trait to the profile.
+///
+/// [`NpcGenerator::generate`] derives the profile from the seed and the
+/// [`StableId`] of the NPC only, so an NPC has the same profile in every run,
+/// no matter how many others were generated before it. The name, the stats
+/// and the traits are drawn from separate streams: adding a stat or a trait
+/// table keeps the names of existing NPCs, and adding a trait table keeps
+/// their stats.
+#[derive(Debug, Clone)]
+pub struct NpcGenerator<T> {
+    /// Generates the names.
+    pub names: NameGenerator,
+
+    /// The names of the stats, and how they are distributed.
+    pub stats: Vec<(String, RandomParams)>,
+
+    /// The tables traits are picked from.
+    pub traits: Vec<WeightedTable<T>>,
+}
+
+impl<T: Clone> NpcGenerator<T> {
+    /// Create a generator without stats or traits.
+    pub fn new(names: NameGenerator) -> Self {
+        Self {
+            names,
+            stats: vec![],
+            traits: vec![],
+        }
+    }
+
+    /// Add a stat in `range`, drawn from a bell curve centered on the middle
+    /// of the range.
+    pub fn with_stat(self, name: impl Into<String>, range: Range<f32>) -> Self {
+        self.with_stat_params(
+            name,
+            RandomParams::new(range).with_curve(Curve::Centered(3)),
+        )
+    }
+
+    /// Add a stat drawn from the params.
+    pub fn with_stat_params(mut self, name: impl Into<String>, params: RandomParams) -> Self {
+        self.stats.push((name.into(), params));
+        self
+    }
+
+    /// Add a table to pick a trait from.
+    pub fn with_traits(mut self, traits: impl IntoIterator<Item = (T, f32)>) -> Self {
+        self.traits.push(traits.into_iter().collect());
+        self
+    }
+
+    /// Roll the profile of the NPC with the given id.
+    ///
+    /// The profile only depends on the seed of `rng` and the id, see
+    /// [`Rng::at`]; no values are drawn from `rng` itself.
+    pub fn generate(&self, rng: &Rng, id: StableId) -> NpcProfile<T> {
+        let key = hash::combine(hash::hash_bytes(b"npc"), id.key());
+
+        NpcProfile {
+            name: self.names.generate(&mut rng.at(key, 0)),
+            stats: self.roll_stats(&mut rng.at(key, 1)),
+            traits: self.roll_traits(&mut rng.at(key, 2)),
+        }
+    }
+
+    /// Roll a profile from the values of `rng`.
+    pub fn generate_with<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> NpcProfile<T> {
+        NpcProfile {
+            name: self.names.generate(rng),
+            stats: self.roll_stats(rng),
+            traits: self.roll_traits(rng),
+        }
+    }
+
+    fn roll_stats<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<(String, f32)> {
+        self.stats
+            .iter()
+            .map(|(name, params)| (name.clone(), rng.sample_params(params)))
+            .collect()
+    }
+
+    fn roll_traits<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec<T> {
+        self.traits
+            .iter()
+            .filter_map(|table| table.pick(rng).cloned())
+            .collect()
+    }
+}
@@ -0,0 +1,171 @@
+use crate::bevy::prelude::*;
+use rand::distributions::Distribution;
+use rand::Rng;
+use std::ops::Range;
+
+/// How values are distributed within the range of [`RandomParams`].
+#[derive(Debug, Clone, Copy)]
+pub enum Curve {
+    /// Every value in the range is equally likely.
+    Uniform,
+
+    /// Values are biased towards the start of the range for exponents above
+    /// `1.0`, and towards the end for exponents below `1.0`.
+    Power(f32),
+
+    /// Values cluster around the middle of the range, more tightly the more
+    /// samples are averaged. `Centered(2)` is a triangular distribution.
+    Centered(u32),
+
+    /// Maps a uniformly distributed `t` in `[0, 1)` to the position within
+    /// the range, which should also be within `[0, 1]`.
+    Custom(fn(f32) -> f32),
+}
+
+impl Curve {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        match self {
+            Self::Uniform => rng.gen(),
+            Self::Power(exponent) => rng.gen::<f32>().powf(*exponent),
+            Self::Centered(samples) => {
+                let samples = (*samples).max(1);
+                (0..samples).map(|_| rng.gen::<f32>()).sum::<f32>() / samples as f32
+            }
+            Self::Custom(curve) => curve(rng.gen()),
+        }
+    }
+}
+
+/// Per-axis ranges used when sampling vectors from [`RandomParams`].
+///
+/// Axes without a range fall back to the range of the params.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AxisParams {
+    /// The range of the `x` axis.
+    pub x: Option<Range<f32>>,
+
+    /// The range of the `y` axis.
+    pub y: Option<Range<f32>>,
+
+    /// The range of the `z` axis.
+    pub z: Option<Range<f32>>,
+}
+
+/// A description of how to randomize a value.
+///
+/// This is the shared vocabulary of the randomization features of this crate:
+/// a range, an optional curve shaping the distribution within the range, and
+/// optional per-axis ranges for vectors.
+///
+/// Unlike `gen_range`, an empty range (e.g. `1.0..1.0`) is allowed, and always
+/// produces its start value, which makes it easy to turn randomization off.
+///
+/// ```rust,ignore
+/// let params = RandomParams::new(0.5..2.0).with_curve(Curve::Centered(2));
+/// let scale = rng.sample_params(&params);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomParams {
+    /// The range values are drawn from.
+    pub value: Range<f32>,
+
+    /// How values are distributed within the range.
+    pub curve: Curve,
+
+    /// Per-axis ranges, used when sampling vectors.
+    pub axes: Option<AxisParams>,
+}
+
+impl RandomParams {
+    /// Create params drawing uniformly from the given range.
+    pub fn new(value: Range<f32>) -> Self {
+        Self {
+            value,
+            curve: Curve::Uniform,
+            axes: None,
+        }
+    }
+
+    /// Create params that always produce the given value.
+    pub fn constant(value: f32) -> Self {
+        Self::new(value..value)
+    }
+
+    /// Use the given curve to distribute values within the range.
+    pub fn with_curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Use the given per-axis ranges when sampling vectors.
+    pub fn with_axes(mut self, axes: AxisParams) -> Self {
+        self.axes = Some(axes);
+        self
+    }
+
+    fn sample_range<R: Rng + ?Sized>(&self, rng: &mut R, range: &Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.curve.sample(rng)
+    }
+
+    fn axis(&self, axis: impl Fn(&AxisParams) -> &Option<Range<f32>>) -> &Range<f32> {
+        self.axes
+            .as_ref()
+            .and_then(|axes| axis(axes).as_ref())
+            .unwrap_or(&self.value)
+    }
+}
+
+impl From<Range<f32>> for RandomParams {
+    fn from(value: Range<f32>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl Distribution<f32> for RandomParams {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        self.sample_range(rng, &self.value)
+    }
+}
+
+impl Distribution<Vec2> for RandomParams {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec2 {
+        Vec2::new(
+            self.sample_range(rng, self.axis(|axes| &axes.x)),
+            self.sample_range(rng, self.axis(|axes| &axes.y)),
+        )
+    }
+}
+
+impl Distribution<Vec3> for RandomParams {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        Vec3::new(
+            self.sample_range(rng, self.axis(|axes| &axes.x)),
+            self.sample_range(rng, self.axis(|axes| &axes.y)),
+            self.sample_range(rng, self.axis(|axes| &axes.z)),
+        )
+    }
+}
+
+/// Sample values described by [`RandomParams`].
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+pub trait RngParamsExt: Rng {
+    /// Returns a value drawn according to the params.
+    fn sample_params(&mut self, params: &RandomParams) -> f32 {
+        params.sample(self)
+    }
+
+    /// Returns a vector drawn according to the params, each axis sampled
+    /// independently.
+    fn sample_params_vec2(&mut self, params: &RandomParams) -> Vec2 {
+        params.sample(self)
+    }
+
+    /// Returns a vector drawn according to the params, each axis sampled
+    /// independently.
+    fn sample_params_vec3(&mut self, params: &RandomParams) -> Vec3 {
+        params.sample(self)
+    }
+}
+
+impl<R: Rng + ?Sized> RngParamsExt for R {}
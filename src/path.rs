@@ -0,0 +1,210 @@
+use crate::bevy::prelude::*;
+use crate::Shape;
+use rand::Rng;
+use std::ops::Range;
+
+/// A smooth curve through a list of points, as a Catmull-Rom spline.
+///
+/// The curve passes through every point, and its tangent at each point is
+/// parallel to the line between the neighbouring points, so there are no
+/// sharp corners. See [`RngPathExt`] to generate random ones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CatmullRom {
+    /// The points the curve passes through, in order.
+    pub points: Vec<Vec3>,
+
+    /// Whether the curve returns from the last point to the first one.
+    pub closed: bool,
+}
+
+impl CatmullRom {
+    /// Create a curve through the given points.
+    pub fn new(points: Vec<Vec3>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    /// The number of segments between points.
+    pub fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            len if self.closed => len,
+            len => len - 1,
+        }
+    }
+
+    /// Returns the point at `t` along the curve, from the first point at
+    /// `t = 0` to the last one (or back to the first one, for closed curves)
+    /// at `t = 1`.
+    ///
+    /// Every segment takes the same share of `t`, regardless of its length.
+    /// An empty curve always returns the origin.
+    pub fn sample(&self, t: f32) -> Vec3 {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.first().copied().unwrap_or(Vec3::ZERO);
+        }
+
+        let position = t.clamp(0.0, 1.0) * segments as f32;
+        let segment = (position as usize).min(segments - 1);
+        let t = position - segment as f32;
+
+        let point = |offset| self.point(segment as isize + offset);
+        let (p0, p1, p2, p3) = (point(-1), point(0), point(1), point(2));
+
+        (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (t * t)
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (t * t * t))
+            * 0.5
+    }
+
+    /// Returns the point with the given index, wrapping around for closed
+    /// curves, and repeating the first and last points for open ones.
+    fn point(&self, index: isize) -> Vec3 {
+        let len = self.points.len() as isize;
+        let index = if self.closed {
+            index.rem_euclid(len)
+        } else {
+            index.clamp(0, len - 1)
+        };
+
+        self.points[index as usize]
+    }
+}
+
+/// A quadratic Bézier curve from `start` to `end`, bent towards `control`.
+///
+/// Projectiles under constant gravity follow exactly this kind of curve.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BezierArc {
+    /// The start of the curve.
+    pub start: Vec3,
+
+    /// The control point the curve bends towards.
+    pub control: Vec3,
+
+    /// The end of the curve.
+    pub end: Vec3,
+}
+
+impl BezierArc {
+    /// Returns the point at `t` along the curve, from `start` at `t = 0` to
+    /// `end` at `t = 1`.
+    pub fn sample(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        let u = 1.0 - t;
+
+        self.start * (u * u) + self.control * (2.0 * u * t) + self.end * (t * t)
+    }
+
+    /// The point halfway along the curve, the apex of a symmetric arc.
+    pub fn apex(&self) -> Vec3 {
+        self.sample(0.5)
+    }
+}
+
+/// Generate smooth random paths, e.g. for patrol routes and projectile arcs.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+///
+/// Each path only depends on its arguments and the state of the rng. Draw
+/// from the [`RngComponent`](crate::RngComponent) of an entity to give it a
+/// route of its own, which stays the same in replays and on every client:
+///
+/// ```rust,ignore
+/// fn plan_patrols(mut guards: Query<(&mut RngComponent, &mut Patrol), Added<Patrol>>) {
+///     let grounds = Shape::Cuboid(Vec3::new(20.0, 0.0, 20.0));
+///
+///     for (mut rng, mut patrol) in guards.iter_mut() {
+///         patrol.route = rng.patrol_route(grounds, patrol.post, 5);
+///     }
+/// }
+/// ```
+///
+/// Points are offset by samples of the shapes as they are. The 2D shapes lie
+/// in the `XY` plane, like Bevy's 2D cameras, which suits 2D games; for
+/// routes on the ground of a 3D scene, in the `XZ` plane, use a
+/// [`Shape::Cuboid`] without height, as above. Arcs rise along `Y` in both
+/// cases.
+pub trait RngPathExt: Rng {
+    /// Returns a closed route through `waypoints` random points within the
+    /// region around `center`.
+    ///
+    /// The points are visited in nearest-neighbour order, starting with the
+    /// first one drawn, which keeps the route from crossing itself in most
+    /// cases.
+    fn patrol_route(&mut self, region: Shape, center: Vec3, waypoints: usize) -> CatmullRom {
+        let mut remaining: Vec<Vec3> = (0..waypoints)
+            .map(|_| center + self.sample(region))
+            .collect();
+
+        let mut points = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let next = match points.last() {
+                Some(&last) => nearest(&remaining, last),
+                None => 0,
+            };
+
+            points.push(remaining.swap_remove(next));
+        }
+
+        CatmullRom::new(points, true)
+    }
+
+    /// Returns a path through the given waypoints, each moved by a random
+    /// offset within the `jitter` shape.
+    fn jittered_path(&mut self, waypoints: &[Vec3], jitter: Shape, closed: bool) -> CatmullRom {
+        let points = waypoints
+            .iter()
+            .map(|&waypoint| waypoint + self.sample(jitter))
+            .collect();
+
+        CatmullRom::new(points, closed)
+    }
+
+    /// Returns an arc from `start` to `end`, whose apex is a random height
+    /// above the midpoint, moved by a random offset within the `jitter`
+    /// shape.
+    fn projectile_arc(
+        &mut self,
+        start: Vec3,
+        end: Vec3,
+        height: Range<f32>,
+        jitter: Shape,
+    ) -> BezierArc {
+        let middle = (start + end) * 0.5;
+        let height = if height.start < height.end {
+            self.gen_range(height)
+        } else {
+            height.start
+        };
+        let apex = middle + Vec3::Y * height + self.sample(jitter);
+
+        // A quadratic Bézier curve passes halfway between its midpoint and
+        // its control point at `t = 0.5`.
+        BezierArc {
+            start,
+            control: apex * 2.0 - middle,
+            end,
+        }
+    }
+}
+
+impl<R: Rng + ?Sized> RngPathExt for R {}
+
+/// Returns the index of the point closest to `target`.
+fn nearest(points: &[Vec3], target: Vec3) -> usize {
+    let mut nearest = 0;
+    let mut distance = f32::INFINITY;
+
+    for (index, &point) in points.iter().enumerate() {
+        let offset = point - target;
+        let candidate = offset.dot(offset);
+        if candidate < distance {
+            nearest = index;
+            distance = candidate;
+        }
+    }
+
+    nearest
+}
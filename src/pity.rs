@@ -0,0 +1,244 @@
+use crate::table::pick_weighted;
+use crate::WeightedTable;
+use rand::Rng;
+
+/// Bad-luck protection for one entry of a [`PityTable`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pity {
+    /// The fraction of its base weight added to the entry for every roll
+    /// that missed it, e.g. `0.5` doubles its chances after two misses.
+    pub boost: f32,
+
+    /// Guarantee the entry once it was missed this many times in a row, e.g.
+    /// with `Some(9)` the entry is picked on the tenth roll at the latest.
+    pub guarantee: Option<u32>,
+}
+
+/// The miss counters of a [`PityTable`].
+///
+/// Store them with the player's save, and restore them with
+/// [`PityTable::set_state`], so bad-luck protection carries over between
+/// sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PityState {
+    /// The number of rolls in a row that missed each entry, in table order.
+    pub misses: Vec<u32>,
+}
+
+/// A [`WeightedTable`] with bad-luck protection.
+///
+/// Entries with a [`Pity`] rule get more likely with every roll that misses
+/// them, and can be guaranteed after a number of misses, which avoids the dry
+/// streaks raw weighted tables produce:
+///
+/// ```rust,ignore
+/// let mut loot = PityTable::new(table).with_pity(
+///     legendary,
+///     Pity {
+///         boost: 0.1,
+///         guarantee: Some(50),
+///     },
+/// );
+///
+/// let drop = loot.roll(&mut rng);
+/// ```
+///
+/// When several guaranteed entries are due at once, the one missed the most
+/// times is picked (the first one on ties), and the others stay due.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PityTable<T> {
+    table: WeightedTable<T>,
+    rules: Vec<Option<Pity>>,
+    state: PityState,
+}
+
+impl<T> PityTable<T> {
+    /// Wrap the table, without any bad-luck protection yet.
+    pub fn new(table: WeightedTable<T>) -> Self {
+        let len = table.len();
+
+        Self {
+            table,
+            rules: vec![None; len],
+            state: PityState {
+                misses: vec![0; len],
+            },
+        }
+    }
+
+    /// Protect the entry at `index` with the given rule.
+    ///
+    /// # Panics
+    ///
+    /// If the table has no entry at `index`.
+    pub fn with_pity(mut self, index: usize, pity: Pity) -> Self {
+        self.set_pity(index, pity);
+        self
+    }
+
+    /// Protect the entry at `index` with the given rule.
+    ///
+    /// # Panics
+    ///
+    /// If the table has no entry at `index`.
+    pub fn set_pity(&mut self, index: usize, pity: Pity) {
+        self.rules[index] = Some(pity);
+    }
+
+    /// The wrapped table.
+    pub fn table(&self) -> &WeightedTable<T> {
+        &self.table
+    }
+
+    /// The current miss counters.
+    pub fn state(&self) -> &PityState {
+        &self.state
+    }
+
+    /// Restore miss counters, e.g. from a save file.
+    ///
+    /// Counters for entries past the end of the table are ignored, and
+    /// missing counters start at zero.
+    pub fn set_state(&mut self, mut state: PityState) {
+        state.misses.resize(self.table.len(), 0);
+        self.state = state;
+    }
+
+    /// The weight of the entry at `index` for the next roll, including its
+    /// boost.
+    ///
+    /// # Panics
+    ///
+    /// If the table has no entry at `index`.
+    pub fn effective_weight(&self, index: usize) -> f32 {
+        let weight = self.table.get(index).map_or(0.0, |(_, weight)| weight);
+
+        match self.rules[index] {
+            Some(pity) => weight * (1.0 + pity.boost * self.state.misses[index] as f32),
+            None => weight,
+        }
+    }
+
+    /// Pick a random entry, and update the miss counters.
+    ///
+    /// Returns `None` if the table has no entry with a positive weight.
+    pub fn roll<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<&T> {
+        let index = match self.due() {
+            Some(index) => index,
+            None => pick_weighted(
+                rng,
+                (0..self.table.len()).map(|index| self.effective_weight(index)),
+            )?,
+        };
+
+        for (entry, misses) in self.state.misses.iter_mut().enumerate() {
+            if entry == index {
+                *misses = 0;
+            } else if self.rules[entry].is_some() {
+                *misses = misses.saturating_add(1);
+            }
+        }
+
+        self.table.get(index).map(|(item, _)| item)
+    }
+
+    /// Returns the guaranteed entry that is due, if any.
+    fn due(&self) -> Option<usize> {
+        let mut due: Option<(usize, u32)> = None;
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            let misses = self.state.misses[index];
+            let guarantee = match rule.and_then(|pity| pity.guarantee) {
+                Some(guarantee) => guarantee,
+                None => continue,
+            };
+
+            let weight = self.table.get(index).map_or(0.0, |(_, weight)| weight);
+            if misses < guarantee || weight <= 0.0 {
+                continue;
+            }
+
+            match due {
+                Some((_, most)) if most >= misses => {}
+                _ => due = Some((index, misses)),
+            }
+        }
+
+        due.map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn table(weights: &[f32]) -> WeightedTable<usize> {
+        weights.iter().copied().enumerate().collect()
+    }
+
+    #[test]
+    fn boost_grows_with_misses() {
+        let mut pity = PityTable::new(table(&[1.0, 2.0])).with_pity(
+            1,
+            Pity {
+                boost: 0.5,
+                guarantee: None,
+            },
+        );
+        assert_eq!(pity.effective_weight(1), 2.0);
+
+        pity.set_state(PityState { misses: vec![4, 2] });
+        assert_eq!(pity.effective_weight(0), 1.0);
+        assert_eq!(pity.effective_weight(1), 4.0);
+    }
+
+    #[test]
+    fn guarantee_after_misses() {
+        let mut pity = PityTable::new(table(&[1.0, 1e-9])).with_pity(
+            1,
+            Pity {
+                boost: 0.0,
+                guarantee: Some(3),
+            },
+        );
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(5);
+        let rolls: Vec<_> = (0..12).map(|_| *pity.roll(&mut rng).unwrap()).collect();
+        assert_eq!(rolls, [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn guarantee_of_zero_is_always_due() {
+        let mut pity = PityTable::new(table(&[1.0, 1e-9])).with_pity(
+            1,
+            Pity {
+                boost: 0.0,
+                guarantee: Some(0),
+            },
+        );
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(5);
+        assert!((0..5).all(|_| pity.roll(&mut rng) == Some(&1)));
+    }
+
+    #[test]
+    fn most_missed_guarantee_first() {
+        let rule = Pity {
+            boost: 0.0,
+            guarantee: Some(1),
+        };
+        let mut pity = PityTable::new(table(&[1e-9, 1e-9, 1.0]))
+            .with_pity(0, rule)
+            .with_pity(1, rule);
+        pity.set_state(PityState { misses: vec![1, 2] });
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(5);
+        assert_eq!(pity.roll(&mut rng), Some(&1));
+        assert_eq!(pity.roll(&mut rng), Some(&0));
+        assert_eq!(pity.state().misses, [0, 1, 0]);
+    }
+}
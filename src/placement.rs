@@ -0,0 +1,100 @@
+use crate::bevy::prelude::*;
+use crate::{RandomParams, Rng, RngParamsExt, Shape, StableId};
+
+/// The `RandomTransform` component varies the placement of its entity once,
+/// then removes itself.
+///
+/// When added, the entity's `Transform` is adjusted:
+///
+/// - its translation is offset by a point drawn from `shape`,
+/// - it is rotated around the y axis by an angle drawn from `yaw` (in
+///   radians),
+/// - its scale is multiplied by a factor drawn from `scale`.
+///
+/// This is ideal to scatter props placed through scenes: add the component
+/// next to the `Transform` of each prop, and every instance ends up slightly
+/// different.
+///
+/// Values are drawn from the rng of the system applying the variation, so
+/// the placement of entities that were added in the same frame depends on
+/// the (unspecified) order they are processed in. Give the entity a
+/// [`StableId`], or set `key` to a value that is stable for the entity, to
+/// draw from [`Rng::at`] instead.
+///
+/// ```rust,ignore
+/// commands.spawn_bundle((
+///     Transform::from_xyz(4.0, 0.0, 2.0),
+///     GlobalTransform::identity(),
+///     RandomTransform::new(Shape::Circle(0.5))
+///         .with_yaw(RandomParams::new(0.0..std::f32::consts::TAU))
+///         .with_scale(RandomParams::new(0.8..1.2)),
+/// ));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomTransform {
+    /// The region the translation offset is drawn from.
+    pub shape: Shape,
+
+    /// The rotation around the y axis, in radians.
+    pub yaw: RandomParams,
+
+    /// The uniform scale factor.
+    pub scale: RandomParams,
+
+    /// A stable key to draw the variation from, see [`Rng::at`]. Takes
+    /// precedence over the [`StableId`] of the entity.
+    pub key: Option<u64>,
+}
+
+impl RandomTransform {
+    /// Create a variation offsetting the translation within `shape`, without
+    /// rotating or scaling.
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            yaw: RandomParams::constant(0.0),
+            scale: RandomParams::constant(1.0),
+            key: None,
+        }
+    }
+
+    /// Rotate around the y axis by an angle drawn from the params.
+    pub fn with_yaw(mut self, yaw: RandomParams) -> Self {
+        self.yaw = yaw;
+        self
+    }
+
+    /// Scale by a factor drawn from the params.
+    pub fn with_scale(mut self, scale: RandomParams) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Draw the variation from the given stable key.
+    pub fn with_key(mut self, key: u64) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    fn apply<R: rand::Rng + ?Sized>(&self, rng: &mut R, transform: &mut Transform) {
+        transform.translation += rng.sample(self.shape);
+        transform.rotation =
+            transform.rotation * Quat::from_rotation_y(rng.sample_params(&self.yaw));
+        transform.scale *= rng.sample_params(&self.scale);
+    }
+}
+
+pub(crate) fn apply_random_transforms(
+    mut commands: Commands,
+    mut rng: Local<Rng>,
+    mut query: Query<(Entity, &RandomTransform, &mut Transform, Option<&StableId>)>,
+) {
+    for (entity, random, mut transform, id) in query.iter_mut() {
+        match random.key.or_else(|| id.map(|id| id.key())) {
+            Some(key) => random.apply(&mut rng.at(key, 0), &mut transform),
+            None => random.apply(&mut *rng, &mut transform),
+        }
+
+        commands.entity(entity).remove::<RandomTransform>();
+    }
+}
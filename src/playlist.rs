@@ -0,0 +1,124 @@
+use crate::{Rng, RngIndexExt};
+
+/// The `ShuffledPlaylist` resource plays a list of handles in a random
+/// order, e.g. music tracks or ambient loops.
+///
+/// Every handle is played once per cycle, in a shuffled order, before the
+/// list is shuffled again. The first handle of a new cycle is never the last
+/// one of the previous cycle, so a handle never plays twice in a row (unless
+/// it is the only one):
+///
+/// ```rust,ignore
+/// app.insert_resource(ShuffledPlaylist::new(seed.stream_rng("music"), tracks));
+///
+/// fn play_music(mut playlist: ResMut<ShuffledPlaylist<Handle<AudioSource>>>, audio: Res<Audio>) {
+///     if let Some(track) = playlist.next() {
+///         audio.play(track);
+///     }
+/// }
+/// ```
+///
+/// The shuffles are drawn from the rng of the playlist. The order within a
+/// cycle is shuffled with indices from [`RngIndexExt::index`], so it doesn't
+/// depend on the version of `rand`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShuffledPlaylist<H> {
+    handles: Vec<H>,
+    order: Vec<usize>,
+    position: usize,
+    last: Option<usize>,
+    rng: Rng,
+}
+
+impl<H: Clone> ShuffledPlaylist<H> {
+    /// Create a playlist of the handles, drawing from the given rng.
+    pub fn new(rng: Rng, handles: impl IntoIterator<Item = H>) -> Self {
+        let handles: Vec<_> = handles.into_iter().collect();
+        let mut playlist = Self {
+            order: (0..handles.len()).collect(),
+            handles,
+            position: 0,
+            last: None,
+            rng,
+        };
+
+        playlist.reshuffle();
+        playlist
+    }
+
+    /// The number of handles.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns `true` if there are no handles.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Returns all handles, in the order they were added.
+    pub fn handles(&self) -> &[H] {
+        &self.handles
+    }
+
+    /// The number of handles left in the current cycle.
+    pub fn remaining(&self) -> usize {
+        self.order.len() - self.position
+    }
+
+    /// Returns the handle `next` will return, without advancing the
+    /// playlist, or `None` at the end of a cycle.
+    pub fn peek(&self) -> Option<&H> {
+        let index = *self.order.get(self.position)?;
+        Some(&self.handles[index])
+    }
+
+    /// Add a handle, at a random position in the rest of the current cycle.
+    pub fn push(&mut self, handle: H) {
+        self.handles.push(handle);
+
+        let position = self.position + self.rng.index(self.remaining() + 1).unwrap_or(0);
+        self.order.insert(position, self.handles.len() - 1);
+    }
+
+    /// Start a new cycle of every handle, in a new order.
+    ///
+    /// The last handle played still isn't played first.
+    pub fn reshuffle(&mut self) {
+        // Fisher-Yates, from the back.
+        for end in (1..self.order.len()).rev() {
+            let other = self.rng.index(end + 1).unwrap_or(0);
+            self.order.swap(end, other);
+        }
+
+        // Swap a repeat with any other handle, keeping the rest of the order
+        // shuffled.
+        if self.order.len() > 1 && self.order.first().copied() == self.last {
+            let other = 1 + self.rng.index(self.order.len() - 1).unwrap_or(0);
+            self.order.swap(0, other);
+        }
+
+        self.position = 0;
+    }
+}
+
+/// Returns the next handle to play, shuffling a new cycle once every handle
+/// was played. The playlist never ends, unless it has no handles.
+impl<H: Clone> Iterator for ShuffledPlaylist<H> {
+    type Item = H;
+
+    fn next(&mut self) -> Option<H> {
+        if self.handles.is_empty() {
+            return None;
+        }
+
+        if self.position == self.order.len() {
+            self.reshuffle();
+        }
+
+        let index = self.order[self.position];
+        self.position += 1;
+        self.last = Some(index);
+        Some(self.handles[index].clone())
+    }
+}
@@ -0,0 +1,117 @@
+use rand::Rng;
+
+/// A chance using the pseudo-random distribution of Warcraft III and Dota.
+///
+/// Rather than rolling the nominal chance every time, the chance starts at a
+/// constant `C` and grows by `C` with every failed check, until a check
+/// succeeds and it starts over. `C` is derived from the nominal chance, so
+/// that the long-run success rate matches it: a 25% critical strike still
+/// happens on a quarter of the hits, but with far fewer long streaks of
+/// successes or failures.
+///
+/// ```rust,ignore
+/// let mut crit = PrdChance::new(0.25);
+///
+/// if crit.check(&mut rng) {
+///     damage *= 2.0;
+/// }
+/// ```
+///
+/// The number of failures is part of the state; keep it with the entity the
+/// chance belongs to, e.g. in a component.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrdChance {
+    chance: f64,
+    constant: f64,
+    failures: u32,
+}
+
+impl PrdChance {
+    /// Create a chance with the given nominal probability, clamped to
+    /// `[0, 1]`.
+    pub fn new(chance: f64) -> Self {
+        let chance = chance.clamp(0.0, 1.0);
+
+        Self {
+            chance,
+            constant: constant_for(chance),
+            failures: 0,
+        }
+    }
+
+    /// The nominal probability of a success.
+    pub fn chance(&self) -> f64 {
+        self.chance
+    }
+
+    /// The constant `C` the probability grows by with every failed check.
+    pub fn constant(&self) -> f64 {
+        self.constant
+    }
+
+    /// The number of failed checks since the last success.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// The probability that the next check succeeds.
+    pub fn current(&self) -> f64 {
+        (self.constant * f64::from(self.failures.saturating_add(1))).min(1.0)
+    }
+
+    /// Roll the chance, drawing exactly one value from `rng`.
+    pub fn check<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        let success = rng.gen::<f64>() < self.current();
+        if success {
+            self.failures = 0;
+        } else {
+            self.failures = self.failures.saturating_add(1);
+        }
+
+        success
+    }
+
+    /// Forget the failures since the last success.
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+}
+
+/// The long-run success rate of a PRD chance with the given constant: the
+/// inverse of the expected number of checks until a success.
+fn chance_for(constant: f64) -> f64 {
+    let mut expected = 0.0;
+    let mut reached = 1.0;
+    let mut checks = 1.0;
+
+    // Stop once the remaining checks no longer affect the result.
+    while reached > 1e-15 {
+        let current = (constant * checks).min(1.0);
+        expected += checks * current * reached;
+        reached *= 1.0 - current;
+        checks += 1.0;
+    }
+
+    1.0 / expected
+}
+
+/// Find the constant matching the nominal chance by bisection, as the rate
+/// grows monotonically with the constant.
+fn constant_for(chance: f64) -> f64 {
+    if chance <= 0.0 || chance >= 1.0 {
+        return chance;
+    }
+
+    let (mut low, mut high) = (0.0, chance);
+    for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if chance_for(middle) < chance {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
@@ -0,0 +1,128 @@
+use crate::index;
+use rand::Rng;
+
+/// A chance that succeeds exactly `successes` times in every window of
+/// `window` checks, in a random order.
+///
+/// Rolling a 15% chance twenty times can produce no successes at all, or
+/// eight. A `QuotaChance::new(3, 20)` produces exactly three in the first
+/// twenty checks, three in the next twenty, and so on, while keeping their
+/// positions within each window unpredictable:
+///
+/// ```rust,ignore
+/// let mut rare_spawn = QuotaChance::new(3, 20);
+///
+/// let kind = if rare_spawn.check(&mut rng) { Kind::Rare } else { Kind::Common };
+/// ```
+///
+/// Each check draws exactly one value, and every order of the successes
+/// within a window is equally likely, as if the window was shuffled up
+/// front. Only the counters are stored; keep them with the spawner the chance
+/// belongs to, e.g. in a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuotaChance {
+    successes: u32,
+    window: u32,
+    remaining_successes: u32,
+    remaining: u32,
+}
+
+impl QuotaChance {
+    /// Create a chance succeeding `successes` times in every `window`
+    /// checks.
+    ///
+    /// The window is at least one check long, and `successes` is clamped to
+    /// the window.
+    pub fn new(successes: u32, window: u32) -> Self {
+        let window = window.max(1);
+        let successes = successes.min(window);
+
+        Self {
+            successes,
+            window,
+            remaining_successes: successes,
+            remaining: window,
+        }
+    }
+
+    /// The number of successes in every window.
+    pub fn successes(&self) -> u32 {
+        self.successes
+    }
+
+    /// The number of checks in every window.
+    pub fn window(&self) -> u32 {
+        self.window
+    }
+
+    /// The number of checks left in the current window.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// The number of successes left in the current window.
+    pub fn remaining_successes(&self) -> u32 {
+        self.remaining_successes
+    }
+
+    /// The probability that the next check succeeds.
+    pub fn current(&self) -> f64 {
+        f64::from(self.remaining_successes) / f64::from(self.remaining)
+    }
+
+    /// Roll the chance, drawing exactly one value from `rng`.
+    pub fn check<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        // Drawing each check with the share of successes left is the same as
+        // drawing from a shuffled window, one position at a time.
+        let draw = index::scale(rng.next_u64(), u64::from(self.remaining));
+        let success = draw < u64::from(self.remaining_successes);
+        if success {
+            self.remaining_successes -= 1;
+        }
+
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.reset();
+        }
+
+        success
+    }
+
+    /// Start a new window.
+    pub fn reset(&mut self) {
+        self.remaining_successes = self.successes;
+        self.remaining = self.window;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn meets_the_quota_every_window() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let mut chance = QuotaChance::new(3, 20);
+        for _ in 0..50 {
+            let successes = (0..20).filter(|_| chance.check(&mut rng)).count();
+            assert_eq!(successes, 3);
+            assert_eq!(chance.remaining(), 20);
+        }
+    }
+
+    #[test]
+    fn draws_one_value() {
+        let mut a = Xoshiro256StarStar::seed_from_u64(2);
+        let mut b = a.clone();
+        let mut chance = QuotaChance::new(1, 3);
+        for _ in 0..100 {
+            chance.check(&mut a);
+            b.next_u64();
+        }
+
+        assert_eq!(a, b);
+    }
+}
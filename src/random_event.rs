@@ -0,0 +1,191 @@
+use crate::bevy::prelude::*;
+use crate::{Rng, RngLabel, RootRng, Seed};
+use rand::Rng as _;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// How often a random event happens on average, see [`RandomEvents`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rate {
+    per_second: f32,
+}
+
+impl Rate {
+    /// The average number of events per second.
+    pub fn events_per_second(&self) -> f32 {
+        self.per_second
+    }
+}
+
+/// A rate of `events` per second.
+pub fn per_second(events: f32) -> Rate {
+    Rate {
+        per_second: events.max(0.0),
+    }
+}
+
+/// A rate of `events` per minute.
+pub fn per_minute(events: f32) -> Rate {
+    per_second(events / 60.0)
+}
+
+/// A random event, emitted by the app at a configured [`Rate`].
+///
+/// The label identifies the stream the event is scheduled from, see
+/// [`RngLabel`] on why to pick an explicit one. `generate` creates the event
+/// when it occurs, drawing any payload from the same stream:
+///
+/// ```rust,ignore
+/// struct MeteorStrike {
+///     angle: f32,
+/// }
+///
+/// impl RngLabel for MeteorStrike {
+///     fn label() -> &'static str {
+///         "meteor strike"
+///     }
+/// }
+///
+/// impl RandomEvent for MeteorStrike {
+///     fn generate(rng: &mut Rng) -> Self {
+///         Self {
+///             angle: rng.gen_range(0.0..std::f32::consts::TAU),
+///         }
+///     }
+/// }
+/// ```
+pub trait RandomEvent: RngLabel {
+    /// Create the event when it occurs.
+    fn generate(rng: &mut Rng) -> Self;
+}
+
+/// The `RandomEvents` resource holds the rates of the random events declared
+/// with [`RandomEventsAppExt::add_random_event`].
+///
+/// Events occur independently of each other, at random times: each event
+/// type is a Poisson process whose intervals are drawn from its own stream,
+/// and whose timer follows the frame time, so the rate is the same at any
+/// frame rate, and several events are emitted in a single frame when it is
+/// long enough. Rates can be changed at any time:
+///
+/// ```rust,ignore
+/// app.add_random_event::<MeteorStrike>(per_minute(0.5));
+///
+/// fn meteor_shower(mut events: ResMut<RandomEvents>) {
+///     events.random::<MeteorStrike>(per_minute(10.0));
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RandomEvents {
+    rates: BTreeMap<&'static str, Rate>,
+}
+
+impl RandomEvents {
+    /// Set the rate of the event. A rate of zero pauses it.
+    ///
+    /// Only events declared with
+    /// [`RandomEventsAppExt::add_random_event`] are emitted.
+    pub fn random<T: RandomEvent>(&mut self, rate: Rate) -> &mut Self {
+        self.rates.insert(T::label(), rate);
+        self
+    }
+
+    /// Returns the rate of the event, if it has one.
+    pub fn rate<T: RandomEvent>(&self) -> Option<Rate> {
+        self.rates.get(T::label()).copied()
+    }
+
+    /// Returns `true` if the event has a rate.
+    pub fn contains<T: RandomEvent>(&self) -> bool {
+        self.rates.contains_key(T::label())
+    }
+}
+
+/// Declare random events on an app.
+pub trait RandomEventsAppExt {
+    /// Emit the event at the given rate, from a stream derived from the seed
+    /// and the label of the event.
+    ///
+    /// Declaring an event again only replaces its rate.
+    fn add_random_event<T: RandomEvent>(&mut self, rate: Rate) -> &mut Self;
+}
+
+impl RandomEventsAppExt for AppBuilder {
+    fn add_random_event<T: RandomEvent>(&mut self, rate: Rate) -> &mut Self {
+        self.init_resource::<RandomEvents>();
+
+        let declared = {
+            let mut events = self
+                .world_mut()
+                .get_resource_mut::<RandomEvents>()
+                .expect("resource was just initialized");
+            let declared = events.contains::<T>();
+            events.random::<T>(rate);
+            declared
+        };
+
+        if !declared {
+            self.add_event::<T>()
+                .add_system(emit_random_events::<T>.system());
+        }
+
+        self
+    }
+}
+
+struct EventTimer<T> {
+    rng: Rng,
+    rate: Rate,
+    remaining: f64,
+    event: PhantomData<fn() -> T>,
+}
+
+impl<T> EventTimer<T> {
+    /// Draw the time until the next event, exponentially distributed.
+    fn reset(&mut self, rate: Rate) {
+        self.rate = rate;
+        self.remaining = match f64::from(rate.per_second) {
+            per_second if per_second > 0.0 => -(1.0 - self.rng.gen::<f64>()).ln() / per_second,
+            _ => f64::INFINITY,
+        };
+    }
+}
+
+impl<T: RandomEvent> FromWorld for EventTimer<T> {
+    fn from_world(world: &mut World) -> Self {
+        let label = format!("random event/{}", T::label());
+        let rng = match world.get_resource::<RootRng>() {
+            Some(root) => root.stream_rng(&label),
+            None => Seed::from_entropy().stream_rng(&label),
+        };
+
+        Self {
+            rng,
+            rate: Rate::default(),
+            remaining: f64::INFINITY,
+            event: PhantomData,
+        }
+    }
+}
+
+fn emit_random_events<T: RandomEvent>(
+    time: Res<Time>,
+    events: Res<RandomEvents>,
+    mut timer: Local<EventTimer<T>>,
+    mut writer: EventWriter<T>,
+) {
+    let rate = events.rate::<T>().unwrap_or_default();
+    // The intervals are memoryless, so a new rate can start a new interval.
+    if rate != timer.rate {
+        timer.reset(rate);
+    }
+
+    timer.remaining -= time.delta_seconds_f64();
+    while timer.remaining <= 0.0 {
+        let overshoot = timer.remaining;
+        writer.send(T::generate(&mut timer.rng));
+        timer.reset(rate);
+        timer.remaining += overshoot;
+    }
+}
@@ -0,0 +1,172 @@
+use crate::table::pick_weighted;
+use crate::{WeightModifier, WeightedTable};
+use rand::Rng;
+
+/// A rarity tier, see [`RarityTiers`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RarityTier {
+    /// The name of the tier, e.g. `"epic"`.
+    pub name: String,
+
+    /// The color items of this tier are displayed with, as sRGB components
+    /// with alpha, e.g. for `Color::rgba`.
+    pub color: [f32; 4],
+
+    /// The base probability of the tier.
+    pub probability: f32,
+}
+
+/// The rarity model of a game: a list of tiers and their base probabilities.
+///
+/// Insert it as a resource so every system generating items shares the same
+/// data-driven model. Probabilities are relative to each other, so they don't
+/// need to add up to one.
+///
+/// ```rust,ignore
+/// let mut tiers = RarityTiers::new();
+/// tiers
+///     .add("common", [0.8, 0.8, 0.8, 1.0], 0.70)
+///     .add("rare", [0.2, 0.4, 1.0, 1.0], 0.25)
+///     .add("legendary", [1.0, 0.6, 0.0, 1.0], 0.05);
+///
+/// let rarity = rng.roll_rarity(&tiers);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RarityTiers {
+    tiers: WeightedTable<RarityTier>,
+}
+
+impl RarityTiers {
+    /// Create a model without any tiers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tier, from the most common to the rarest.
+    pub fn add(&mut self, name: impl Into<String>, color: [f32; 4], probability: f32) -> &mut Self {
+        let tier = RarityTier {
+            name: name.into(),
+            color,
+            probability,
+        };
+
+        self.tiers.add(tier, probability);
+        self
+    }
+
+    /// The number of tiers.
+    pub fn len(&self) -> usize {
+        self.tiers.len()
+    }
+
+    /// Returns `true` if there are no tiers.
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    /// Returns the tier at the given index.
+    pub fn get(&self, index: usize) -> Option<&RarityTier> {
+        self.tiers.get(index).map(|(tier, _)| tier)
+    }
+
+    /// Returns the index of the tier with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.iter().position(|tier| tier.name == name)
+    }
+
+    /// Iterate over the tiers, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &RarityTier> {
+        self.tiers.iter().map(|(tier, _)| tier)
+    }
+}
+
+/// Roll rarities from a [`RarityTiers`] model.
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+pub trait RngRarityExt: Rng {
+    /// Returns the index of a random tier.
+    ///
+    /// Returns `None` if no tier has a positive probability.
+    fn roll_rarity(&mut self, tiers: &RarityTiers) -> Option<usize> {
+        tiers.tiers.pick_index(self)
+    }
+
+    /// Returns the index of a random tier, with the probabilities adjusted by
+    /// `modifier`, e.g. to apply a luck stat.
+    fn roll_rarity_with<M>(&mut self, tiers: &RarityTiers, modifier: &M) -> Option<usize>
+    where
+        M: WeightModifier<RarityTier> + ?Sized,
+    {
+        tiers.tiers.pick_index_with(self, modifier)
+    }
+}
+
+impl<R: Rng + ?Sized> RngRarityExt for R {}
+
+/// A loot table driven by a [`RarityTiers`] model.
+///
+/// A roll first picks a tier, then an item of that tier from its own
+/// [`WeightedTable`]. Tiers without items are never rolled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RarityTable<T> {
+    tiers: RarityTiers,
+    items: Vec<WeightedTable<T>>,
+}
+
+impl<T> RarityTable<T> {
+    /// Create a table without items for the given tiers.
+    pub fn new(tiers: RarityTiers) -> Self {
+        let items = (0..tiers.len()).map(|_| WeightedTable::new()).collect();
+
+        Self { tiers, items }
+    }
+
+    /// Add an item to the tier at the given index, with a weight relative to
+    /// the other items of the tier.
+    pub fn add(&mut self, tier: usize, item: T, weight: f32) -> &mut Self {
+        self.items[tier].add(item, weight);
+        self
+    }
+
+    /// The rarity model of the table.
+    pub fn tiers(&self) -> &RarityTiers {
+        &self.tiers
+    }
+
+    /// Returns the items of the tier at the given index.
+    pub fn items(&self, tier: usize) -> Option<&WeightedTable<T>> {
+        self.items.get(tier)
+    }
+
+    /// Roll a tier and an item of that tier, and return both.
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<(&RarityTier, &T)> {
+        self.roll_with(rng, &|_: &RarityTier, probability: f32| probability)
+    }
+
+    /// Roll a tier and an item of that tier, with the tier probabilities
+    /// adjusted by `modifier`.
+    pub fn roll_with<R, M>(&self, rng: &mut R, modifier: &M) -> Option<(&RarityTier, &T)>
+    where
+        R: Rng + ?Sized,
+        M: WeightModifier<RarityTier> + ?Sized,
+    {
+        let weights =
+            self.tiers
+                .tiers
+                .iter()
+                .zip(&self.items)
+                .map(|((tier, probability), items)| {
+                    if items.total_weight() > 0.0 {
+                        modifier.modify(tier, probability)
+                    } else {
+                        0.0
+                    }
+                });
+
+        let tier = pick_weighted(rng, weights)?;
+        let item = self.items[tier].pick(rng)?;
+
+        Some((self.tiers.get(tier)?, item))
+    }
+}
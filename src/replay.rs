@@ -0,0 +1,363 @@
+use crate::{Rng, Seed};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// The bytes every replay file starts with.
+pub const REPLAY_MAGIC: [u8; 4] = *b"BRNG";
+
+/// The replay format version written by [`save_replay`].
+///
+/// [`load_replay`] also reads files written with earlier versions, but rejects
+/// newer ones, so a replay recorded with one version of this crate is never
+/// silently misread by another.
+///
+/// Version 2 added byte seeds.
+pub const REPLAY_VERSION: u16 = 2;
+
+const SEED_NUMBER: u8 = 0;
+const SEED_STRING: u8 = 1;
+const SEED_BYTES: u8 = 2;
+
+/// A recorded replay: the seed a session ran with, and a draw log per stream.
+///
+/// Record a replay by calling [`Replay::record`] for each rng at a fixed point
+/// (e.g. the end of every tick). Replaying the session with the same seed
+/// should then produce an identical log, and the first log that differs
+/// points at the stream that diverged.
+///
+/// Logs are kept per stream name. Unnamed rngs, like every `Local<Rng>`, have
+/// no name to tell them apart, so record them under a label of your own with
+/// [`Replay::record_as`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Replay {
+    /// The seed the session ran with.
+    pub seed: Seed,
+
+    /// The draw log of each recorded stream.
+    pub streams: Vec<StreamLog>,
+}
+
+/// The draw log of a single stream.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StreamLog {
+    /// The name of the stream, or the label given to
+    /// [`Replay::record_as`].
+    ///
+    /// This is only `None` in replays recorded by earlier versions of this
+    /// crate, which logged every unnamed rng together.
+    pub stream: Option<String>,
+
+    /// The total number of draws, sampled at every call to [`Replay::record`].
+    pub draws: Vec<u64>,
+}
+
+impl Replay {
+    /// Create an empty replay for the given seed.
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            seed,
+            streams: vec![],
+        }
+    }
+
+    /// Append the current draw count of `rng` to the log of its stream.
+    ///
+    /// Returns `false`, without recording anything, if the rng doesn't belong
+    /// to a named stream, see [`Replay::record_as`].
+    pub fn record(&mut self, rng: &Rng) -> bool {
+        let info = rng.seed_info();
+
+        match info.stream {
+            Some(stream) => {
+                self.push(stream, info.draws);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Append the current draw count of `rng` to the log with the given
+    /// label, e.g. the name of the system owning an unnamed rng.
+    ///
+    /// Labels share their names with the streams logged by
+    /// [`Replay::record`], so pick labels that aren't stream names.
+    pub fn record_as(&mut self, label: &str, rng: &Rng) {
+        self.push(label.to_owned(), rng.seed_info().draws);
+    }
+
+    fn push(&mut self, stream: String, draws: u64) {
+        let stream = Some(stream);
+
+        match self.streams.iter_mut().find(|log| log.stream == stream) {
+            Some(log) => log.draws.push(draws),
+            None => self.streams.push(StreamLog {
+                stream,
+                draws: vec![draws],
+            }),
+        }
+    }
+}
+
+/// The error returned by [`load_replay`].
+#[derive(Debug)]
+pub enum ReplayError {
+    /// Reading the replay failed.
+    Io(io::Error),
+
+    /// The data doesn't start with [`REPLAY_MAGIC`], it isn't a replay.
+    InvalidMagic,
+
+    /// The replay was written with an unsupported format version.
+    UnsupportedVersion(u16),
+
+    /// The replay data is malformed.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read replay: {}", err),
+            Self::InvalidMagic => f.write_str("not a replay file"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported replay version {} (expected at most {})",
+                version, REPLAY_VERSION
+            ),
+            Self::Corrupt(reason) => write!(f, "corrupt replay: {}", reason),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Corrupt("unexpected end of data"),
+            _ => Self::Io(err),
+        }
+    }
+}
+
+/// Write a replay in the versioned replay format.
+///
+/// The format is: [`REPLAY_MAGIC`], [`REPLAY_VERSION`], the seed, and the
+/// stream logs. All integers are little-endian, so files can be exchanged
+/// between platforms.
+pub fn save_replay<W: Write>(mut writer: W, replay: &Replay) -> io::Result<()> {
+    writer.write_all(&REPLAY_MAGIC)?;
+    writer.write_all(&REPLAY_VERSION.to_le_bytes())?;
+
+    write_seed(&mut writer, &replay.seed)?;
+    write_len(&mut writer, replay.streams.len())?;
+    for log in &replay.streams {
+        match &log.stream {
+            Some(stream) => {
+                writer.write_all(&[1])?;
+                write_str(&mut writer, stream)?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        write_len(&mut writer, log.draws.len())?;
+        for draws in &log.draws {
+            writer.write_all(&draws.to_le_bytes())?;
+        }
+    }
+
+    writer.flush()
+}
+
+/// Read a replay written by [`save_replay`].
+///
+/// Data that isn't a replay, or was written in a newer format version, is
+/// rejected with an error instead of being misinterpreted.
+pub fn load_replay<R: Read>(mut reader: R) -> Result<Replay, ReplayError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != REPLAY_MAGIC {
+        return Err(ReplayError::InvalidMagic);
+    }
+
+    let version = read_u16(&mut reader)?;
+    if version == 0 || version > REPLAY_VERSION {
+        return Err(ReplayError::UnsupportedVersion(version));
+    }
+
+    let seed = read_seed(&mut reader, version >= 2)?;
+
+    let mut streams = vec![];
+    for _ in 0..read_u32(&mut reader)? {
+        let stream = match read_u8(&mut reader)? {
+            0 => None,
+            1 => Some(read_str(&mut reader)?),
+            _ => return Err(ReplayError::Corrupt("invalid stream name")),
+        };
+
+        let mut draws = vec![];
+        for _ in 0..read_u32(&mut reader)? {
+            draws.push(read_u64(&mut reader)?);
+        }
+
+        streams.push(StreamLog { stream, draws });
+    }
+
+    Ok(Replay { seed, streams })
+}
+
+pub(crate) fn write_seed<W: Write>(writer: &mut W, seed: &Seed) -> io::Result<()> {
+    match seed {
+        Seed::Number(num) => {
+            writer.write_all(&[SEED_NUMBER])?;
+            writer.write_all(&num.to_le_bytes())
+        }
+        Seed::String(seed) => {
+            writer.write_all(&[SEED_STRING])?;
+            write_str(writer, seed)
+        }
+        Seed::Bytes(bytes) => {
+            writer.write_all(&[SEED_BYTES])?;
+            write_bytes(writer, bytes)
+        }
+    }
+}
+
+pub(crate) fn read_seed<R: Read>(reader: &mut R, bytes: bool) -> Result<Seed, ReplayError> {
+    match read_u8(reader)? {
+        SEED_NUMBER => Ok(Seed::Number(read_u64(reader)?)),
+        SEED_STRING => Ok(Seed::String(read_str(reader)?)),
+        SEED_BYTES if bytes => Ok(Seed::Bytes(read_bytes(reader)?)),
+        _ => Err(ReplayError::Corrupt("unknown seed kind")),
+    }
+}
+
+pub(crate) fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    if len > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "replay section too long",
+        ));
+    }
+
+    writer.write_all(&(len as u32).to_le_bytes())
+}
+
+pub(crate) fn write_bytes<W: Write>(writer: &mut W, value: &[u8]) -> io::Result<()> {
+    write_len(writer, value.len())?;
+    writer.write_all(value)
+}
+
+pub(crate) fn write_str<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_bytes(writer, value.as_bytes())
+}
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(crate) fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, ReplayError> {
+    let len = u64::from(read_u32(reader)?);
+
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(ReplayError::Corrupt("unexpected end of data"));
+    }
+
+    Ok(bytes)
+}
+
+pub(crate) fn read_str<R: Read>(reader: &mut R) -> Result<String, ReplayError> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| ReplayError::Corrupt("invalid utf-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn logs_streams_and_labels_apart() {
+        let seed = Seed::from("replay");
+        let mut loot = seed.stream_rng("loot");
+        let mut ai = seed.tick_rng(0);
+        let mut particles = seed.tick_rng(1);
+
+        let mut replay = Replay::new(seed);
+        for _ in 0..3 {
+            loot.next_u64();
+            ai.next_u64();
+            assert!(replay.record(&loot));
+            assert!(!replay.record(&ai));
+            replay.record_as("ai", &ai);
+            replay.record_as("particles", &particles);
+        }
+        particles.next_u64();
+
+        let logs: Vec<_> = replay
+            .streams
+            .iter()
+            .map(|log| (log.stream.as_deref(), log.draws.clone()))
+            .collect();
+        assert_eq!(
+            logs,
+            [
+                (Some("loot"), vec![1, 2, 3]),
+                (Some("ai"), vec![1, 2, 3]),
+                (Some("particles"), vec![0, 0, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_load() {
+        let seeds = [Seed::from(7), Seed::from("abc"), Seed::from(vec![1, 2, 3])];
+        for seed in seeds.iter() {
+            let mut replay = Replay::new(seed.clone());
+            replay.record_as("ai", &replay.seed.tick_rng(0));
+            replay.streams.push(StreamLog {
+                stream: None,
+                draws: vec![4, 8],
+            });
+
+            let mut bytes = vec![];
+            save_replay(&mut bytes, &replay).unwrap();
+            assert_eq!(load_replay(&bytes[..]).unwrap(), replay);
+
+            bytes.truncate(bytes.len() - 1);
+            assert!(matches!(
+                load_replay(&bytes[..]),
+                Err(ReplayError::Corrupt(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::table::pick_weighted;
+use crate::{hash, CounterRng, WeightedTable};
+use rand::Rng;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step<T> {
+    Group {
+        name: String,
+        outcomes: WeightedTable<T>,
+    },
+    Picks {
+        name: String,
+        outcomes: WeightedTable<T>,
+        count: RangeInclusive<u32>,
+    },
+    Bonus {
+        name: String,
+        chance: f64,
+        outcome: T,
+    },
+}
+
+impl<T> Step<T> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Group { name, .. } | Self::Picks { name, .. } | Self::Bonus { name, .. } => name,
+        }
+    }
+}
+
+/// Resolves a full result from several rolls in one pass, e.g. the rarity,
+/// the affixes and the bonus properties of an item.
+///
+/// The resolver is a list of steps, resolved in the order they were added:
+///
+/// - a group picks exactly one of its mutually exclusive outcomes, by
+///   weight,
+/// - picks draw a number of outcomes from a table, never the same one twice,
+/// - a bonus adds its outcome with a chance, independently of everything
+///   else.
+///
+/// ```rust,ignore
+/// let items = OutcomeResolver::new()
+///     .with_group("rarity", vec![(Loot::Common, 70.0), (Loot::Rare, 25.0), (Loot::Epic, 5.0)])
+///     .with_picks("affix", affixes, 1..=3)
+///     .with_bonus("socket", 0.1, Loot::Socket);
+///
+/// let item = items.resolve(&mut rng);
+/// let rarity = item.get("rarity");
+/// let affixes: Vec<_> = item.all("affix").collect();
+/// ```
+///
+/// Resolving draws exactly one value from the rng, and each step draws from
+/// its own [`CounterRng`], keyed by the step's name. The same rng state
+/// always resolves the same result, and adding, removing or changing a step
+/// never shifts the rolls of the other steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutcomeResolver<T> {
+    steps: Vec<Step<T>>,
+}
+
+impl<T> Default for OutcomeResolver<T> {
+    fn default() -> Self {
+        Self { steps: vec![] }
+    }
+}
+
+impl<T: Clone> OutcomeResolver<T> {
+    /// Create a resolver without steps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a group of mutually exclusive outcomes, one of which is picked by
+    /// weight.
+    ///
+    /// Nothing is added to the result if no outcome has a positive weight.
+    pub fn with_group(
+        mut self,
+        name: impl Into<String>,
+        outcomes: impl IntoIterator<Item = (T, f32)>,
+    ) -> Self {
+        self.steps.push(Step::Group {
+            name: name.into(),
+            outcomes: outcomes.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Add a number of outcomes drawn from `count`, picked by weight without
+    /// repetition.
+    ///
+    /// Fewer outcomes are added when the table runs out of outcomes with a
+    /// positive weight.
+    pub fn with_picks(
+        mut self,
+        name: impl Into<String>,
+        outcomes: WeightedTable<T>,
+        count: RangeInclusive<u32>,
+    ) -> Self {
+        self.steps.push(Step::Picks {
+            name: name.into(),
+            outcomes,
+            count,
+        });
+        self
+    }
+
+    /// Add an outcome with the given chance, independently of the other
+    /// steps.
+    pub fn with_bonus(mut self, name: impl Into<String>, chance: f64, outcome: T) -> Self {
+        self.steps.push(Step::Bonus {
+            name: name.into(),
+            chance,
+            outcome,
+        });
+        self
+    }
+
+    /// Resolve every step, in the order they were added.
+    pub fn resolve<R: Rng + ?Sized>(&self, rng: &mut R) -> Resolution<T> {
+        let round = rng.next_u64();
+        let mut outcomes = vec![];
+        let mut occurrences = HashMap::new();
+
+        for step in &self.steps {
+            // Steps sharing a name are told apart by how many came before.
+            let occurrence = occurrences.entry(step.name()).or_insert(0);
+            let key = hash::combine(hash::hash_bytes(step.name().as_bytes()), *occurrence);
+            *occurrence += 1;
+
+            let rng = &mut CounterRng::new(round, key, 0);
+            match step {
+                Step::Group {
+                    name,
+                    outcomes: table,
+                } => {
+                    if let Some(outcome) = table.pick(rng) {
+                        outcomes.push((name.clone(), outcome.clone()));
+                    }
+                }
+                Step::Picks {
+                    name,
+                    outcomes: table,
+                    count,
+                } => {
+                    let count = rng.gen_range(*count.start()..=(*count.end()).max(*count.start()));
+
+                    let mut picked = vec![false; table.len()];
+                    for _ in 0..count {
+                        let weights =
+                            table
+                                .iter()
+                                .zip(&picked)
+                                .map(|((_, weight), picked)| if *picked { 0.0 } else { weight });
+
+                        let index = match pick_weighted(rng, weights) {
+                            Some(index) => index,
+                            None => break,
+                        };
+
+                        picked[index] = true;
+                        if let Some((outcome, _)) = table.get(index) {
+                            outcomes.push((name.clone(), outcome.clone()));
+                        }
+                    }
+                }
+                Step::Bonus {
+                    name,
+                    chance,
+                    outcome,
+                } => {
+                    if rng.gen::<f64>() < *chance {
+                        outcomes.push((name.clone(), outcome.clone()));
+                    }
+                }
+            }
+        }
+
+        Resolution { outcomes }
+    }
+}
+
+/// The result of [`OutcomeResolver::resolve`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution<T> {
+    outcomes: Vec<(String, T)>,
+}
+
+impl<T> Resolution<T> {
+    /// Returns the first outcome of the step with the given name.
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.outcomes
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Returns every outcome of the steps with the given name, in the order
+    /// they were resolved.
+    pub fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a T> + 'a {
+        self.outcomes
+            .iter()
+            .filter(move |(other, _)| other == name)
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Returns `true` if a step with the given name added an outcome.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns the names and outcomes of all steps, in the order they were
+    /// resolved.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.outcomes
+            .iter()
+            .map(|(name, outcome)| (name.as_str(), outcome))
+    }
+
+    /// Returns the outcomes, with the names of their steps.
+    pub fn into_outcomes(self) -> Vec<(String, T)> {
+        self.outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn resolver() -> OutcomeResolver<&'static str> {
+        let affixes = vec![
+            ("sharp", 1.0),
+            ("heavy", 1.0),
+            ("swift", 1.0),
+            ("cursed", 0.0),
+        ];
+
+        OutcomeResolver::new()
+            .with_group("rarity", vec![("common", 7.0), ("rare", 3.0)])
+            .with_picks("affix", affixes.into_iter().collect(), 1..=5)
+            .with_bonus("socket", 0.5, "socket")
+    }
+
+    #[test]
+    fn draws_one_value() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        resolver().resolve(&mut rng);
+
+        let mut expected = Xoshiro256StarStar::seed_from_u64(1);
+        expected.next_u64();
+        assert_eq!(rng, expected);
+    }
+
+    #[test]
+    fn resolves_every_step() {
+        for seed in 0..50 {
+            let resolution = resolver().resolve(&mut Xoshiro256StarStar::seed_from_u64(seed));
+
+            assert_eq!(resolution.all("rarity").count(), 1);
+
+            let affixes: Vec<_> = resolution.all("affix").collect();
+            assert!(!affixes.is_empty() && affixes.len() <= 3);
+            assert!(!affixes.contains(&&"cursed"));
+            for (index, affix) in affixes.iter().enumerate() {
+                assert!(!affixes[index + 1..].contains(affix));
+            }
+        }
+    }
+
+    #[test]
+    fn steps_are_independent() {
+        let extended = OutcomeResolver::new()
+            .with_group("quality", vec![("worn", 1.0), ("fine", 1.0)])
+            .with_group("empty", vec![("never", 0.0)]);
+        let extended = resolver()
+            .steps
+            .into_iter()
+            .fold(extended, |mut resolver, step| {
+                resolver.steps.push(step);
+                resolver
+            });
+
+        for seed in 0..50 {
+            let base = resolver().resolve(&mut Xoshiro256StarStar::seed_from_u64(seed));
+            let resolution = extended.resolve(&mut Xoshiro256StarStar::seed_from_u64(seed));
+
+            assert!(!resolution.contains("empty"));
+            for name in ["rarity", "affix", "socket"].iter() {
+                assert!(base.all(name).eq(resolution.all(name)));
+            }
+        }
+    }
+}
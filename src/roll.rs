@@ -0,0 +1,146 @@
+use crate::{index, RngFloatExt};
+use rand::RngCore;
+use std::ops::Range;
+
+/// A roll resolved by [`RngRollExt::pre_roll`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Roll {
+    /// Succeeds with the given probability.
+    Chance(f64),
+
+    /// An integer in `[start, end)`, uniformly distributed.
+    Range(Range<i64>),
+
+    /// A float in `[start, end)`, uniformly distributed.
+    Float(Range<f64>),
+}
+
+impl Roll {
+    /// Resolve the roll, drawing exactly one value. Empty ranges produce
+    /// their start.
+    pub fn resolve<R: RngCore + ?Sized>(&self, rng: &mut R) -> RollResult {
+        match self {
+            Self::Chance(chance) => RollResult::Chance(rng.f64_01() < *chance),
+            Self::Range(range) => {
+                let len = range.end.saturating_sub(range.start).max(0) as u64;
+                // Not `RngIndexExt::index`, which may redraw.
+                let offset = index::scale(rng.next_u64(), len) as i64;
+                RollResult::Range(range.start + offset)
+            }
+            Self::Float(range) => {
+                let t = rng.f64_01();
+                RollResult::Float(range.start + (range.end - range.start).max(0.0) * t)
+            }
+        }
+    }
+}
+
+/// The outcome of a [`Roll`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RollResult {
+    /// Whether a chance succeeded.
+    Chance(bool),
+
+    /// The integer of a range.
+    Range(i64),
+
+    /// The float of a float range.
+    Float(f64),
+}
+
+/// The named outcomes of [`RngRollExt::pre_roll`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RollSet {
+    results: Vec<(String, RollResult)>,
+}
+
+impl RollSet {
+    /// Returns the outcome of the roll with the given label.
+    pub fn get(&self, label: &str) -> Option<RollResult> {
+        self.results
+            .iter()
+            .find(|(other, _)| other == label)
+            .map(|(_, result)| *result)
+    }
+
+    /// Returns whether the chance with the given label succeeded, or `None`
+    /// if there is no such chance.
+    pub fn check(&self, label: &str) -> Option<bool> {
+        match self.get(label)? {
+            RollResult::Chance(success) => Some(success),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer of the range with the given label, or `None` if
+    /// there is no such range.
+    pub fn int(&self, label: &str) -> Option<i64> {
+        match self.get(label)? {
+            RollResult::Range(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the float of the float range with the given label, or `None`
+    /// if there is no such range.
+    pub fn float(&self, label: &str) -> Option<f64> {
+        match self.get(label)? {
+            RollResult::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the labels and outcomes of all rolls, in the order they were
+    /// resolved.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, RollResult)> {
+        self.results
+            .iter()
+            .map(|(label, result)| (label.as_str(), *result))
+    }
+
+    /// The number of rolls.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns `true` if there are no rolls.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
+
+/// Resolve randomness up front.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng).
+pub trait RngRollExt: RngCore {
+    /// Resolve every roll, in the order given, and return their outcomes by
+    /// label.
+    ///
+    /// Resolving all the randomness of a turn at a single point keeps it
+    /// deterministic, no matter which outcomes the game logic ends up reading
+    /// or in which order:
+    ///
+    /// ```rust,ignore
+    /// let rolls = rng.pre_roll(&[("crit", Roll::Chance(0.2)), ("dmg", Roll::Range(5..10))]);
+    ///
+    /// let mut damage = rolls.int("dmg").unwrap();
+    /// if rolls.check("crit").unwrap() {
+    ///     damage *= 2;
+    /// }
+    /// ```
+    ///
+    /// Exactly one value is drawn per roll, so the values drawn after the
+    /// call only depend on the number of rolls. When a label is used twice,
+    /// [`RollSet::get`] returns the first outcome.
+    fn pre_roll(&mut self, rolls: &[(&str, Roll)]) -> RollSet {
+        let results = rolls
+            .iter()
+            .map(|(label, roll)| (label.to_string(), roll.resolve(self)))
+            .collect();
+
+        RollSet { results }
+    }
+}
+
+impl<R: RngCore + ?Sized> RngRollExt for R {}
@@ -0,0 +1,326 @@
+use crate::bevy::prelude::*;
+use crate::{RandomParams, RngIndexExt, RngParamsExt, SpawnTarget, WeightedTable};
+use rand::Rng;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// The number of candidates tried around a point before it is given up on.
+const CANDIDATES: u32 = 30;
+
+/// A function returning the density at a point of the region.
+pub type DensityFn = Arc<dyn Fn(Vec2) -> f32 + Send + Sync>;
+
+/// How densely a [`Scatter`] fills each part of its region.
+///
+/// Densities range from `0.0` (nothing is placed) to `1.0` (points are packed
+/// as tightly as the spacing allows), and are clamped to that range.
+#[derive(Clone)]
+pub enum DensityMap {
+    /// The same density everywhere.
+    Constant(f32),
+
+    /// A grid of densities stretched over the whole region, e.g. painted in
+    /// a texture. Each point uses the density of the cell it falls into.
+    Grid {
+        /// The number of cells on each axis.
+        size: UVec2,
+
+        /// The densities of the cells, row by row, starting at the lowest
+        /// coordinates.
+        values: Vec<f32>,
+    },
+
+    /// The density at each point, as returned by the function.
+    Fn(DensityFn),
+}
+
+impl DensityMap {
+    /// Create a map returning the density of each point.
+    pub fn from_fn(density: impl Fn(Vec2) -> f32 + Send + Sync + 'static) -> Self {
+        Self::Fn(Arc::new(density))
+    }
+
+    /// Create a grid from one byte per cell, mapping `0` to `0.0` and `255` to
+    /// `1.0`, e.g. the `data` of an `R8Unorm` texture.
+    pub fn from_luma(size: UVec2, bytes: &[u8]) -> Self {
+        Self::Grid {
+            size,
+            values: bytes.iter().map(|byte| f32::from(*byte) / 255.0).collect(),
+        }
+    }
+
+    /// Returns the density at `point`, at `uv` within the region, where
+    /// `(0, 0)` is its lowest corner and `(1, 1)` its highest one.
+    ///
+    /// Cells missing from a grid have a density of `0.0`.
+    pub fn density(&self, point: Vec2, uv: Vec2) -> f32 {
+        let density = match self {
+            Self::Constant(density) => *density,
+            Self::Grid { size, values } => {
+                let x = ((uv.x * size.x as f32) as u32).min(size.x.saturating_sub(1));
+                let y = ((uv.y * size.y as f32) as u32).min(size.y.saturating_sub(1));
+                let index = y as usize * size.x as usize + x as usize;
+                values.get(index).copied().unwrap_or(0.0)
+            }
+            Self::Fn(density) => density(point),
+        };
+
+        density.clamp(0.0, 1.0)
+    }
+}
+
+impl Default for DensityMap {
+    fn default() -> Self {
+        Self::Constant(1.0)
+    }
+}
+
+impl fmt::Debug for DensityMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant(density) => f.debug_tuple("Constant").field(density).finish(),
+            Self::Grid { size, values } => f
+                .debug_struct("Grid")
+                .field("size", size)
+                .field("values", values)
+                .finish(),
+            Self::Fn(_) => f.debug_tuple("Fn").finish(),
+        }
+    }
+}
+
+/// A prop placed by [`Scatter::generate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScatteredProp<'a, T> {
+    /// Where the prop is placed, relative to the center of the region.
+    pub transform: Transform,
+
+    /// The prop picked from the table.
+    pub prop: &'a T,
+}
+
+/// Scatters props over a rectangular region of the ground, e.g. trees, rocks
+/// and grass.
+///
+/// Points are spread with Poisson disk sampling, so no two are closer than
+/// `spacing`, but they don't line up on a grid either. The `density` map
+/// then thins them out: each point is kept with the density at its position.
+/// Each prop is picked from the weighted `props` table, rotated around the y
+/// axis by a random angle, and scaled by a factor drawn from `scale`:
+///
+/// ```rust,ignore
+/// let forest = Scatter::new(Vec2::new(200.0, 200.0), 4.0, props)
+///     .with_density(DensityMap::from_luma(UVec2::new(64, 64), &mask.data))
+///     .with_scale(RandomParams::new(0.8..1.3));
+///
+/// forest.spawn(&mut rng, &mut commands, Vec3::ZERO);
+/// ```
+///
+/// The region lies in the `XZ` plane, the ground of 3D scenes, and is
+/// centered on the origin. Props are generated from the rng alone, so the
+/// same rng state always produces the same placements; draw from a
+/// [`StreamRng`](crate::StreamRng) or [`Rng::at`](crate::Rng::at) of the
+/// chunk for the scatter to only depend on the seed.
+#[derive(Debug, Clone)]
+pub struct Scatter<T> {
+    /// The half extents of the region, on the x and z axes.
+    pub region: Vec2,
+
+    /// The minimum distance between two props.
+    pub spacing: f32,
+
+    /// How densely each part of the region is filled.
+    pub density: DensityMap,
+
+    /// The props to place.
+    pub props: WeightedTable<T>,
+
+    /// The uniform scale factor of each prop.
+    pub scale: RandomParams,
+}
+
+impl<T> Scatter<T> {
+    /// Create a scatter filling the region as densely as `spacing` allows,
+    /// without scaling the props.
+    ///
+    /// The spacing is clamped to at least a hundredth of a unit.
+    pub fn new(region: Vec2, spacing: f32, props: WeightedTable<T>) -> Self {
+        Self {
+            region,
+            spacing,
+            density: DensityMap::default(),
+            props,
+            scale: RandomParams::constant(1.0),
+        }
+    }
+
+    /// Thin out the props with the given density map.
+    pub fn with_density(mut self, density: DensityMap) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Scale the props by a factor drawn from the params.
+    pub fn with_scale(mut self, scale: RandomParams) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Place the props, relative to the center of the region.
+    ///
+    /// Nothing is placed if the table has no prop with a positive weight.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<ScatteredProp<'_, T>> {
+        if self.props.total_weight() <= 0.0 {
+            return vec![];
+        }
+
+        let size = self.region * 2.0;
+        let min = -self.region;
+
+        poisson_disk(rng, size, self.spacing.max(0.01))
+            .into_iter()
+            .filter_map(|point| {
+                let uv = Vec2::new(ratio(point.x, size.x), ratio(point.y, size.y));
+                let point = min + point;
+                if rng.gen::<f32>() >= self.density.density(point, uv) {
+                    return None;
+                }
+
+                let prop = self.props.pick(rng)?;
+                let transform = Transform {
+                    translation: Vec3::new(point.x, 0.0, point.y),
+                    rotation: Quat::from_rotation_y(rng.gen_range(0.0..TAU)),
+                    scale: Vec3::splat(rng.sample_params(&self.scale)),
+                };
+
+                Some(ScatteredProp { transform, prop })
+            })
+            .collect()
+    }
+}
+
+impl Scatter<SpawnTarget> {
+    /// Place the props around `origin`, and spawn them.
+    ///
+    /// Returns the number of props spawned.
+    pub fn spawn<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        commands: &mut Commands,
+        origin: Vec3,
+    ) -> usize {
+        let props = self.generate(rng);
+        for ScatteredProp { transform, prop } in &props {
+            let transform = Transform {
+                translation: origin + transform.translation,
+                ..*transform
+            };
+
+            prop.spawn_at(commands, transform);
+        }
+
+        props.len()
+    }
+}
+
+fn ratio(value: f32, size: f32) -> f32 {
+    if size > 0.0 {
+        value / size
+    } else {
+        0.0
+    }
+}
+
+/// Returns points in `[0, size]` that are at least `spacing` apart, with
+/// Bridson's algorithm.
+fn poisson_disk<R: Rng + ?Sized>(rng: &mut R, size: Vec2, spacing: f32) -> Vec<Vec2> {
+    if size.x < 0.0 || size.y < 0.0 {
+        return vec![];
+    }
+
+    // Each cell is small enough to hold at most one point.
+    let cell = spacing / std::f32::consts::SQRT_2;
+    let columns = (size.x / cell) as usize + 1;
+    let rows = (size.y / cell) as usize + 1;
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let cell_of = |point: Vec2| ((point.x / cell) as usize, (point.y / cell) as usize);
+
+    let first = Vec2::new(rng.gen::<f32>() * size.x, rng.gen::<f32>() * size.y);
+    let (x, y) = cell_of(first);
+    grid[y * columns + x] = Some(0);
+
+    let mut points = vec![first];
+    let mut active = vec![0];
+
+    while !active.is_empty() {
+        let slot = rng.index(active.len()).unwrap_or(0);
+        let center = points[active[slot]];
+
+        let found = (0..CANDIDATES).find_map(|_| {
+            let angle = rng.gen_range(0.0..TAU);
+            let distance = rng.gen_range(spacing..spacing * 2.0);
+            let candidate = center + Vec2::new(angle.cos(), angle.sin()) * distance;
+
+            if candidate.x < 0.0
+                || candidate.y < 0.0
+                || candidate.x > size.x
+                || candidate.y > size.y
+            {
+                return None;
+            }
+
+            let (x, y) = cell_of(candidate);
+            let is_free = (y.saturating_sub(2)..(y + 3).min(rows)).all(|y| {
+                (x.saturating_sub(2)..(x + 3).min(columns)).all(|x| match grid[y * columns + x] {
+                    Some(other) => points[other].distance(candidate) >= spacing,
+                    None => true,
+                })
+            });
+
+            if is_free {
+                Some((candidate, y * columns + x))
+            } else {
+                None
+            }
+        });
+
+        match found {
+            Some((candidate, index)) => {
+                grid[index] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+            }
+            None => {
+                active.swap_remove(slot);
+            }
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn poisson_disk_keeps_its_spacing() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1);
+        let size = Vec2::new(40.0, 25.0);
+        let points = poisson_disk(&mut rng, size, 3.0);
+        assert!(points.len() > 10);
+
+        for (index, point) in points.iter().enumerate() {
+            assert!(point.x >= 0.0 && point.y >= 0.0 && point.x <= size.x && point.y <= size.y);
+            for other in &points[index + 1..] {
+                assert!(point.distance(*other) >= 3.0);
+            }
+        }
+
+        let mut again = Xoshiro256StarStar::seed_from_u64(1);
+        assert_eq!(poisson_disk(&mut again, size, 3.0), points);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::bevy::prelude::*;
+use rand::distributions::Distribution;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// A region to pick random points from, centered on the origin.
+///
+/// The 2D shapes lie in the `XY` plane, as used by Bevy's 2D cameras.
+///
+/// Sample a point with `rng.sample(&shape)`; points are uniformly distributed
+/// over the area (or volume) of the shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// Always the origin.
+    Point,
+
+    /// A rectangle with the given half extents.
+    Rect(Vec2),
+
+    /// A disc with the given radius.
+    Circle(f32),
+
+    /// A box with the given half extents.
+    Cuboid(Vec3),
+
+    /// A ball with the given radius.
+    Sphere(f32),
+}
+
+impl Distribution<Vec3> for Shape {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        match *self {
+            Self::Point => Vec3::ZERO,
+            Self::Rect(half) => Vec3::new(
+                rng.gen_range(-half.x..=half.x),
+                rng.gen_range(-half.y..=half.y),
+                0.0,
+            ),
+            Self::Circle(radius) => {
+                let distance = radius * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..TAU);
+                Vec3::new(distance * angle.cos(), distance * angle.sin(), 0.0)
+            }
+            Self::Cuboid(half) => Vec3::new(
+                rng.gen_range(-half.x..=half.x),
+                rng.gen_range(-half.y..=half.y),
+                rng.gen_range(-half.z..=half.z),
+            ),
+            Self::Sphere(radius) => {
+                let z: f32 = rng.gen_range(-1.0..=1.0);
+                let angle = rng.gen_range(0.0..TAU);
+                let ring = (1.0 - z * z).sqrt();
+                let distance = radius * rng.gen::<f32>().cbrt();
+                Vec3::new(ring * angle.cos(), ring * angle.sin(), z) * distance
+            }
+        }
+    }
+}
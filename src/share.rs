@@ -0,0 +1,403 @@
+use crate::{hash, Seed};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The share code format version written by [`ShareCode::encode`].
+pub const SHARE_CODE_VERSION: u8 = 1;
+
+/// The Crockford base32 alphabet: no `I`, `L`, `O` or `U`, which are easily
+/// confused with other characters.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const KIND_NUMBER: u8 = 0;
+const KIND_STRING: u8 = 1;
+const KIND_BYTES: u8 = 2;
+const HAS_FLAGS: u8 = 0b100;
+
+/// The number of characters between two dashes of an encoded code.
+const GROUP: usize = 4;
+
+/// A seed, and optionally the flags of a ruleset, as a short code players
+/// can share, e.g. for daily challenges or seed races.
+///
+/// Codes use Crockford's base32 alphabet, grouped by dashes:
+///
+/// ```rust,ignore
+/// let code = ShareCode::new(Seed::Number(42)).with_flags(HARD_MODE).encode();
+///
+/// let shared: ShareCode = code.parse()?;
+/// app.add_plugin(RngPlugin::from(shared.seed));
+/// ```
+///
+/// Parsing is forgiving about how players type codes: letters are
+/// case-insensitive, `I` and `L` read as `1`, `O` reads as `0`, and dashes
+/// and spaces are ignored. Every code carries a checksum, so a mistyped
+/// character is reported as an error rather than silently producing another
+/// seed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShareCode {
+    /// The seed of the run.
+    pub seed: Seed,
+
+    /// Ruleset flags chosen by the game, e.g. difficulty modifiers. Flags
+    /// only make the code longer when they aren't zero.
+    pub flags: u64,
+}
+
+impl ShareCode {
+    /// Create a code for the seed, without flags.
+    pub fn new(seed: Seed) -> Self {
+        Self { seed, flags: 0 }
+    }
+
+    /// Set the ruleset flags.
+    pub fn with_flags(mut self, flags: u64) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Encode the seed and flags as a share code.
+    pub fn encode(&self) -> String {
+        let kind = match self.seed {
+            Seed::Number(_) => KIND_NUMBER,
+            Seed::String(_) => KIND_STRING,
+            Seed::Bytes(_) => KIND_BYTES,
+        };
+
+        let mut bytes = vec![SHARE_CODE_VERSION << 4 | kind];
+        if self.flags != 0 {
+            bytes[0] |= HAS_FLAGS;
+            write_varint(&mut bytes, self.flags);
+        }
+
+        match &self.seed {
+            Seed::Number(num) => write_varint(&mut bytes, *num),
+            Seed::String(seed) => bytes.extend_from_slice(seed.as_bytes()),
+            Seed::Bytes(seed) => bytes.extend_from_slice(seed),
+        }
+
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+
+        let mut code = String::new();
+        for (index, symbol) in to_base32(&bytes).into_iter().enumerate() {
+            if index > 0 && index % GROUP == 0 {
+                code.push('-');
+            }
+
+            code.push(ALPHABET[symbol as usize] as char);
+        }
+
+        code
+    }
+
+    /// Parse a share code.
+    pub fn decode(code: &str) -> Result<Self, ShareCodeError> {
+        let mut symbols = Vec::new();
+        for (position, character) in code.trim().chars().enumerate() {
+            match decode_symbol(character) {
+                Some(symbol) => symbols.push(symbol),
+                None if character == '-' || character.is_whitespace() => {}
+                None => {
+                    return Err(ShareCodeError::InvalidCharacter {
+                        character,
+                        position,
+                    })
+                }
+            }
+        }
+
+        let mut bytes = from_base32(&symbols).ok_or(ShareCodeError::Malformed("invalid length"))?;
+        if bytes.len() < 3 {
+            return Err(ShareCodeError::Malformed("too short"));
+        }
+
+        let expected = bytes.split_off(bytes.len() - 2);
+        if checksum(&bytes) != expected[..] {
+            return Err(ShareCodeError::ChecksumMismatch);
+        }
+
+        let header = bytes[0];
+        let version = header >> 4;
+        if version == 0 || version > SHARE_CODE_VERSION {
+            return Err(ShareCodeError::UnsupportedVersion(version));
+        }
+
+        let mut payload = &bytes[1..];
+        let flags = match header & HAS_FLAGS {
+            0 => 0,
+            _ => read_varint(&mut payload)?,
+        };
+
+        let seed = match header & 0b11 {
+            KIND_NUMBER => {
+                let num = read_varint(&mut payload)?;
+                if !payload.is_empty() {
+                    return Err(ShareCodeError::Malformed("trailing data"));
+                }
+
+                Seed::Number(num)
+            }
+            KIND_STRING => match std::str::from_utf8(payload) {
+                Ok(seed) => Seed::String(seed.to_owned()),
+                Err(_) => return Err(ShareCodeError::Malformed("invalid string seed")),
+            },
+            KIND_BYTES => Seed::Bytes(payload.to_vec()),
+            _ => return Err(ShareCodeError::Malformed("unknown seed kind")),
+        };
+
+        Ok(Self { seed, flags })
+    }
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for ShareCode {
+    type Err = ShareCodeError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::decode(code)
+    }
+}
+
+impl From<Seed> for ShareCode {
+    fn from(seed: Seed) -> Self {
+        Self::new(seed)
+    }
+}
+
+impl Seed {
+    /// Encode the seed as a share code, see [`ShareCode`].
+    pub fn share_code(&self) -> String {
+        ShareCode::new(self.clone()).encode()
+    }
+
+    /// Parse a share code, ignoring any ruleset flags, see [`ShareCode`].
+    pub fn from_share_code(code: &str) -> Result<Self, ShareCodeError> {
+        ShareCode::decode(code).map(|code| code.seed)
+    }
+}
+
+/// The error returned when parsing a [`ShareCode`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShareCodeError {
+    /// The code contains a character that isn't part of the alphabet.
+    InvalidCharacter {
+        /// The character.
+        character: char,
+
+        /// The position of the character in the code, counting from zero.
+        position: usize,
+    },
+
+    /// The checksum doesn't match, the code was most likely mistyped.
+    ChecksumMismatch,
+
+    /// The code was created with an unsupported format version.
+    UnsupportedVersion(u8),
+
+    /// The code is malformed.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {:?} at position {} of share code",
+                character, position
+            ),
+            Self::ChecksumMismatch => {
+                f.write_str("share code checksum doesn't match, check the code for typos")
+            }
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported share code version {} (expected at most {})",
+                version, SHARE_CODE_VERSION
+            ),
+            Self::Malformed(reason) => write!(f, "malformed share code: {}", reason),
+        }
+    }
+}
+
+impl Error for ShareCodeError {}
+
+fn checksum(bytes: &[u8]) -> [u8; 2] {
+    let hash = hash::combine(hash::hash_bytes(b"share code"), hash::hash_bytes(bytes));
+    [hash as u8, (hash >> 8) as u8]
+}
+
+fn decode_symbol(character: char) -> Option<u8> {
+    let symbol = match character.to_ascii_uppercase() {
+        'O' => b'0',
+        'I' | 'L' => b'1',
+        character if character.is_ascii_alphanumeric() => character as u8,
+        _ => return None,
+    };
+
+    ALPHABET
+        .iter()
+        .position(|&candidate| candidate == symbol)
+        .map(|symbol| symbol as u8)
+}
+
+fn to_base32(bytes: &[u8]) -> Vec<u8> {
+    let mut symbols = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for &byte in bytes {
+        buffer = buffer << 8 | u32::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            symbols.push((buffer >> bits) as u8 & 0x1f);
+        }
+    }
+
+    if bits > 0 {
+        symbols.push((buffer << (5 - bits)) as u8 & 0x1f);
+    }
+
+    symbols
+}
+
+/// Returns `None` if the symbols don't make up a whole number of bytes.
+fn from_base32(symbols: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+
+    for &symbol in symbols {
+        buffer = buffer << 5 | u32::from(symbol);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    // Padding is shorter than a symbol, and only made of zero bits.
+    if bits < 5 && buffer & ((1 << bits) - 1) == 0 {
+        Some(bytes)
+    } else {
+        None
+    }
+}
+
+/// Append `value` as a LEB128 varint, so small numbers take few characters.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, ShareCodeError> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or(ShareCodeError::Malformed("unexpected end of data"))?;
+        *bytes = rest;
+
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(ShareCodeError::Malformed("number too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes() -> Vec<ShareCode> {
+        let seeds = vec![
+            Seed::Number(0),
+            Seed::Number(u64::MAX),
+            Seed::from("daily-2021-05-01"),
+            Seed::from(""),
+            Seed::from(vec![0, 1, 2, 255]),
+        ];
+
+        seeds
+            .into_iter()
+            .flat_map(|seed| {
+                vec![
+                    ShareCode::new(seed.clone()),
+                    ShareCode::new(seed).with_flags(0b101),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        for code in codes() {
+            let encoded = code.encode();
+            assert!(encoded
+                .chars()
+                .all(|c| c == '-' || ALPHABET.contains(&(c as u8))));
+            assert_eq!(encoded.parse::<ShareCode>().unwrap(), code);
+            assert_eq!(Seed::from_share_code(&encoded).unwrap(), code.seed);
+        }
+    }
+
+    #[test]
+    fn forgives_typing() {
+        for code in codes() {
+            let encoded = code.encode();
+
+            let typed: String = encoded
+                .chars()
+                .filter(|c| *c != '-')
+                .map(|c| match c {
+                    '0' => 'o',
+                    '1' => 'L',
+                    c => c.to_ascii_lowercase(),
+                })
+                .collect();
+            assert_eq!(ShareCode::decode(&format!("  {} ", typed)).unwrap(), code);
+
+            let spaced = encoded.replace('-', " ").replace('1', "i");
+            assert_eq!(ShareCode::decode(&spaced).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn rejects_typos() {
+        for code in codes() {
+            let encoded = code.encode();
+
+            for index in (0..encoded.len()).filter(|index| encoded.as_bytes()[*index] != b'-') {
+                let mut typo = encoded.clone().into_bytes();
+                typo[index] = if typo[index] == b'7' { b'8' } else { b'7' };
+                let typo = String::from_utf8(typo).unwrap();
+
+                assert_ne!(ShareCode::decode(&typo), Ok(code.clone()), "{}", typo);
+            }
+        }
+
+        let code = Seed::Number(42).share_code();
+        assert_eq!(
+            ShareCode::decode(&format!("{}U", code)),
+            Err(ShareCodeError::InvalidCharacter {
+                character: 'U',
+                position: code.len(),
+            })
+        );
+    }
+}
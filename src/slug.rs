@@ -0,0 +1,42 @@
+use rand::RngCore;
+
+/// The characters of a slug: Crockford's base32, lowercased, which leaves
+/// out `i`, `l`, `o` and `u` so slugs are easy to read out and type.
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+/// The number of characters of a slug, five bits each.
+pub const SLUG_LEN: usize = 10;
+
+/// Generate identifiers for procedural content.
+///
+/// This is implemented for every `rand::RngCore`, including
+/// [`Rng`](crate::Rng). Each call draws exactly one value from the rng.
+pub trait RngSlugExt: RngCore {
+    /// Returns a short identifier, e.g. `"k3v9q0x2mz"`.
+    ///
+    /// The slug has [`SLUG_LEN`] characters, holding 50 random bits: among a
+    /// million slugs, the chance of any two being the same is below one in a
+    /// thousand.
+    ///
+    /// Slugs are drawn from the rng like any other value, so the same seed
+    /// always yields the same slugs, which makes them safe to reference from
+    /// save files and to share. Draw them from [`Rng::at`](crate::Rng::at)
+    /// with a stable key for them to not depend on what else was generated
+    /// before.
+    fn slug(&mut self) -> String {
+        let bits = self.next_u64() >> 14;
+
+        (0..SLUG_LEN)
+            .rev()
+            .map(|index| ALPHABET[(bits >> (index * 5)) as usize & 31] as char)
+            .collect()
+    }
+
+    /// Returns a [`slug`](RngSlugExt::slug) with a prefix naming the kind of
+    /// content, e.g. `"planet-k3v9q0x2mz"`.
+    fn content_id(&mut self, prefix: &str) -> String {
+        format!("{}-{}", prefix, self.slug())
+    }
+}
+
+impl<R: RngCore + ?Sized> RngSlugExt for R {}
@@ -0,0 +1,68 @@
+use crate::table::pick_weighted;
+use rand::Rng;
+
+/// Pick items by score, with a temperature.
+///
+/// This is implemented for every `rand::Rng`, including
+/// [`Rng`](crate::Rng).
+pub trait RngSoftmaxExt: Rng {
+    /// Pick an item with a probability following the softmax of the scores,
+    /// or `None` if there are none.
+    ///
+    /// The temperature controls how much the scores matter: at `0.0`, the
+    /// item with the highest score is always picked (the first one, on a
+    /// tie). Higher temperatures flatten the distribution, until every item
+    /// is about as likely as any other:
+    ///
+    /// ```rust,ignore
+    /// let moves = [(Move::Attack, 2.0), (Move::Defend, 1.5), (Move::Flee, -1.0)];
+    ///
+    /// let careful = rng.softmax_pick(&moves, 0.1);
+    /// let erratic = rng.softmax_pick(&moves, 5.0);
+    /// ```
+    ///
+    /// Unlike the weights of a [`WeightedTable`](crate::WeightedTable),
+    /// scores can be negative, and only their differences matter. Items with
+    /// a score of NaN are never picked.
+    ///
+    /// Exactly one value is drawn, whatever the temperature, so tuning it
+    /// doesn't shift the values drawn afterwards.
+    fn softmax_pick<'a, T>(&mut self, items: &'a [(T, f32)], temperature: f32) -> Option<&'a T> {
+        let max = items
+            .iter()
+            .map(|(_, score)| *score)
+            .filter(|score| !score.is_nan())
+            .fold(None, |max: Option<f32>, score| {
+                Some(max.map_or(score, |max| max.max(score)))
+            });
+
+        let max = match max {
+            Some(max) => max,
+            None => {
+                self.next_u64();
+                return None;
+            }
+        };
+
+        if temperature <= 0.0 || max.is_infinite() {
+            self.next_u64();
+            return items
+                .iter()
+                .find(|(_, score)| *score == max)
+                .map(|(item, _)| item);
+        }
+
+        let weights = items.iter().map(|(_, score)| {
+            if score.is_nan() {
+                0.0
+            } else {
+                ((score - max) / temperature).exp()
+            }
+        });
+        let index = pick_weighted(self, weights)?;
+
+        Some(&items[index].0)
+    }
+}
+
+impl<R: Rng + ?Sized> RngSoftmaxExt for R {}
@@ -0,0 +1,146 @@
+use crate::bevy::prelude::*;
+use crate::{RandomParams, Rng, RngParamsExt, Shape, WeightedTable};
+use rand::Rng as _;
+use std::fmt;
+use std::sync::Arc;
+
+/// A function spawning something at the given transform.
+pub type SpawnFn = Arc<dyn Fn(&mut Commands, Transform) + Send + Sync>;
+
+/// What a [`RandomizedSpawner`] spawns.
+#[derive(Clone)]
+pub enum SpawnTarget {
+    /// Spawn the scene as the child of a new entity at the spawn point.
+    Scene(Handle<Scene>),
+
+    /// Call the function with the transform of the spawn point, e.g. to spawn
+    /// a bundle.
+    Spawn(SpawnFn),
+}
+
+impl SpawnTarget {
+    /// Create a target spawning whatever the function spawns.
+    pub fn spawn(spawn: impl Fn(&mut Commands, Transform) + Send + Sync + 'static) -> Self {
+        Self::Spawn(Arc::new(spawn))
+    }
+
+    pub(crate) fn spawn_at(&self, commands: &mut Commands, transform: Transform) {
+        match self {
+            Self::Scene(scene) => {
+                let scene = scene.clone();
+                commands
+                    .spawn_bundle((transform, GlobalTransform::from(transform)))
+                    .with_children(|parent| {
+                        parent.spawn_scene(scene);
+                    });
+            }
+            Self::Spawn(spawn) => spawn(commands, transform),
+        }
+    }
+}
+
+impl fmt::Debug for SpawnTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Scene(scene) => f.debug_tuple("Scene").field(scene).finish(),
+            Self::Spawn(_) => f.debug_tuple("Spawn").finish(),
+        }
+    }
+}
+
+impl From<Handle<Scene>> for SpawnTarget {
+    fn from(scene: Handle<Scene>) -> Self {
+        Self::Scene(scene)
+    }
+}
+
+/// The `RandomizedSpawner` component periodically spawns a random target at a
+/// random point around its entity.
+///
+/// - The time between spawns is drawn from `interval` (in seconds) after each
+///   spawn, so a constant interval gives a regular spawner.
+/// - The target is picked from the weighted `targets` table.
+/// - The spawn point is drawn from `shape`, relative to the `GlobalTransform`
+///   of the spawner entity (if it has one).
+///
+/// Each spawner draws from its own rng, typically forked from a system's rng
+/// with [`Rng::fork`], so spawners don't affect each other, regardless of the
+/// order in which they are processed.
+///
+/// Intervals are clamped to at least a millisecond.
+#[derive(Debug, Clone)]
+pub struct RandomizedSpawner {
+    /// The time between two spawns, in seconds.
+    pub interval: RandomParams,
+
+    /// The region spawn points are drawn from.
+    pub shape: Shape,
+
+    /// The targets to spawn.
+    pub targets: WeightedTable<SpawnTarget>,
+
+    rng: Rng,
+    elapsed: f32,
+    next: f32,
+}
+
+impl RandomizedSpawner {
+    /// Create a spawner drawing from the given rng.
+    pub fn new(
+        mut rng: Rng,
+        interval: RandomParams,
+        shape: Shape,
+        targets: WeightedTable<SpawnTarget>,
+    ) -> Self {
+        let next = roll_interval(&mut rng, &interval);
+
+        Self {
+            interval,
+            shape,
+            targets,
+            rng,
+            elapsed: 0.0,
+            next,
+        }
+    }
+
+    /// The time left until the next spawn, in seconds.
+    pub fn remaining(&self) -> f32 {
+        (self.next - self.elapsed).max(0.0)
+    }
+
+    fn update(&mut self, commands: &mut Commands, origin: Vec3, delta: f32) {
+        self.elapsed += delta;
+
+        while self.elapsed >= self.next {
+            self.elapsed -= self.next;
+            self.spawn(commands, origin);
+            self.next = roll_interval(&mut self.rng, &self.interval);
+        }
+    }
+
+    fn spawn(&mut self, commands: &mut Commands, origin: Vec3) {
+        let translation = origin + self.rng.sample(self.shape);
+        let transform = Transform::from_translation(translation);
+
+        if let Some(target) = self.targets.pick(&mut self.rng) {
+            target.spawn_at(commands, transform);
+        }
+    }
+}
+
+fn roll_interval(rng: &mut Rng, interval: &RandomParams) -> f32 {
+    rng.sample_params(interval).max(0.001)
+}
+
+pub(crate) fn run_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spawners: Query<(&mut RandomizedSpawner, Option<&GlobalTransform>)>,
+) {
+    for (mut spawner, transform) in spawners.iter_mut() {
+        let origin = transform.map_or(Vec3::ZERO, |transform| transform.translation);
+
+        spawner.update(&mut commands, origin, time.delta_seconds());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::hash;
+
+/// The `StableId` component identifies an entity for its random streams.
+///
+/// `Entity` ids are recycled on despawn and depend on the order entities are
+/// spawned in, so two clients (or two runs) rarely agree on them. Randomness
+/// keyed by a `StableId` instead stays the same for "the same" entity, no
+/// matter when it was spawned, or how often it was despawned and respawned.
+///
+/// The per-entity features of this crate, like [`RandomTransform`], draw
+/// from the `StableId` of their entity when it has one. Use the id as the
+/// key of [`Rng::at`](crate::Rng::at) for your own per-entity values.
+///
+/// Derive ids from something your game already agrees on, e.g. a name from
+/// a level file, or the id of a parent and the index of the child:
+///
+/// ```rust,ignore
+/// let camp = StableId::from_name("bandit camp 3");
+///
+/// for index in 0..5 {
+///     commands.spawn_bundle((Bandit, camp.child(index)));
+/// }
+/// ```
+///
+/// When entities are spawned in the same order on every client, the
+/// [`StableIdAllocator`] resource can number them instead.
+///
+/// [`RandomTransform`]: crate::RandomTransform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StableId(pub u64);
+
+impl StableId {
+    /// Derive an id from a name.
+    pub fn from_name(name: &str) -> Self {
+        Self(hash::combine(
+            hash::hash_bytes(b"stable id"),
+            hash::hash_bytes(name.as_bytes()),
+        ))
+    }
+
+    /// Derive the id of the `index`-th child of this id.
+    pub fn child(self, index: u64) -> Self {
+        Self(hash::combine(self.0, index))
+    }
+
+    /// The key to draw this entity's values from, see
+    /// [`Rng::at`](crate::Rng::at).
+    pub fn key(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for StableId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The `StableIdAllocator` resource hands out [`StableId`]s by counting.
+///
+/// The ids only depend on the number of ids allocated before, so they are
+/// stable as long as every client allocates them in the same order, e.g.
+/// from a single system reading a level file. Ids are never reused, so an
+/// entity can keep its id when it is respawned.
+///
+/// The count is hashed with a tag of its own, like [`StableId::from_name`]
+/// does with names, so allocated ids don't collide with the ids you create
+/// with `StableId::from(u64)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StableIdAllocator {
+    next: u64,
+}
+
+impl StableIdAllocator {
+    pub(crate) fn with_allocated(allocated: u64) -> Self {
+        Self { next: allocated }
+    }
+
+    /// Returns a new id.
+    pub fn allocate(&mut self) -> StableId {
+        let id = StableId(hash::combine(hash::hash_bytes(b"allocated id"), self.next));
+        self.next += 1;
+        id
+    }
+
+    /// The number of ids allocated so far.
+    pub fn allocated(&self) -> u64 {
+        self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocated_ids_are_namespaced() {
+        let mut ids = StableIdAllocator::default();
+        let allocated: Vec<_> = (0..100).map(|_| ids.allocate()).collect();
+
+        assert_eq!(ids.allocated(), 100);
+        for (index, id) in allocated.iter().enumerate() {
+            assert!(!allocated[index + 1..].contains(id));
+        }
+
+        for n in 0..100 {
+            assert!(!allocated.contains(&StableId::from(n)));
+        }
+
+        let mut resumed = StableIdAllocator::with_allocated(50);
+        assert_eq!(resumed.allocate(), allocated[50]);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::bevy::prelude::*;
+use crate::{hash, Backend, Rng, RngLabel, RootRng, Seed};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A per-system rng whose stream is keyed by a label.
+///
+/// Every `Local<Rng>` starts from the same root rng, so two systems drawing
+/// from one would see the same values. A `StreamRng` is derived from the seed
+/// and the label of `L` instead: it only depends on the label, not on when
+/// (or whether) any other system is registered, so adding or reordering
+/// unrelated systems never changes the sequence an existing system receives.
+///
+/// ```rust,ignore
+/// struct Loot;
+///
+/// impl RngLabel for Loot {
+///     fn label() -> &'static str {
+///         "loot"
+///     }
+/// }
+///
+/// fn drop_loot(mut rng: Local<StreamRng<Loot>>) {
+///     let roll: f32 = rng.gen();
+/// }
+/// ```
+///
+/// The derivation only uses the hashing primitives of this crate, so content
+/// generated from a given seed and label stays the same across releases, as
+/// long as the label does. See [`RngLabel`] on why to pick an explicit label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamRng<L: RngLabel> {
+    rng: Rng,
+    label: PhantomData<fn() -> L>,
+}
+
+impl<L: RngLabel> StreamRng<L> {
+    /// Returns the underlying rng.
+    pub fn into_inner(self) -> Rng {
+        self.rng
+    }
+}
+
+impl<L: RngLabel> Deref for StreamRng<L> {
+    type Target = Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl<L: RngLabel> DerefMut for StreamRng<L> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+impl<L: RngLabel> FromWorld for StreamRng<L> {
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world.get_resource::<RootRng>() {
+            Some(root) => root.stream_rng(L::label()),
+            None => Seed::from_entropy().stream_rng(L::label()),
+        };
+
+        Self {
+            rng,
+            label: PhantomData,
+        }
+    }
+}
+
+impl Seed {
+    /// Returns the rng of the stream with the given label.
+    ///
+    /// This is the rng a [`StreamRng`] with that label starts with, which
+    /// lets you compute it outside of a system.
+    ///
+    /// The rng uses the default [`Backend`], see [`Rng::backend`].
+    pub fn stream_rng(&self, label: &str) -> Rng {
+        self.stream_rng_with(Backend::default(), label)
+    }
+
+    pub(crate) fn stream_rng_with(&self, backend: Backend, label: &str) -> Rng {
+        Rng::derived(
+            backend,
+            self.clone(),
+            Some(label.to_owned()),
+            stream_key(label),
+        )
+    }
+}
+
+/// The key the rng of the stream with the given label is derived from.
+pub(crate) fn stream_key(label: &str) -> u64 {
+    hash::combine(
+        hash::hash_bytes(b"stream"),
+        hash::hash_bytes(label.as_bytes()),
+    )
+}
+
+/// The `RngStreams` resource holds the rngs of the streams declared with
+/// [`RngPluginBuilder::streams`](crate::RngPluginBuilder::streams).
+///
+/// Each rng produces the same values as a [`StreamRng`] with the same label,
+/// but is shared by every system accessing the resource, and follows seed
+/// swaps through [`SeedSync`](crate::SeedSync).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RngStreams {
+    streams: BTreeMap<String, Rng>,
+}
+
+impl RngStreams {
+    pub(crate) fn new<'a>(root: &RootRng, labels: impl IntoIterator<Item = &'a String>) -> Self {
+        let streams = labels
+            .into_iter()
+            .map(|label| (label.clone(), root.stream_rng(label)))
+            .collect();
+
+        Self { streams }
+    }
+
+    /// Returns the rng of the stream with the given label, if it was declared.
+    pub fn get(&self, label: &str) -> Option<&Rng> {
+        self.streams.get(label)
+    }
+
+    /// Returns the rng of the stream with the given label, if it was declared.
+    pub fn get_mut(&mut self, label: &str) -> Option<&mut Rng> {
+        self.streams.get_mut(label)
+    }
+
+    /// Returns the labels of all declared streams, in alphabetical order.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.streams.keys().map(String::as_str)
+    }
+}
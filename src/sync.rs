@@ -0,0 +1,64 @@
+use crate::bevy::prelude::*;
+use crate::{FrameRng, RootRng, Seed, TickRng};
+
+/// The `SeedSync` resource replaces the seed of a running app.
+///
+/// This is meant for multiplayer lobbies, where the host distributes a seed
+/// and every client adopts it: schedule the received seed, and it is applied
+/// at the start of the next frame, before any fixed tick runs. All rngs are
+/// swapped at once: the [`Seed`], [`FrameRng`] and [`TickRng`] resources, and
+/// every `Local<Rng>` (which start over from the new seed at their next draw).
+///
+/// A [`SeedChanged`] event confirms the swap.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SeedSync {
+    pending: Option<Seed>,
+}
+
+impl SeedSync {
+    /// Schedule the seed to be applied at the start of the next frame.
+    ///
+    /// Scheduling another seed before then replaces the pending one.
+    pub fn schedule(&mut self, seed: Seed) {
+        self.pending = Some(seed);
+    }
+
+    /// The seed that will be applied at the start of the next frame, if any.
+    pub fn pending(&self) -> Option<&Seed> {
+        self.pending.as_ref()
+    }
+}
+
+/// The event sent once a seed scheduled through [`SeedSync`] is applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeedChanged {
+    /// The seed that was replaced.
+    pub previous: Seed,
+
+    /// The seed now in use.
+    pub seed: Seed,
+}
+
+pub(crate) fn apply_seed(
+    mut sync: ResMut<SeedSync>,
+    mut seed: ResMut<Seed>,
+    root: Res<RootRng>,
+    mut frame_rng: ResMut<FrameRng>,
+    mut tick_rng: ResMut<TickRng>,
+    mut changes: EventWriter<SeedChanged>,
+) {
+    let next = match sync.pending.take() {
+        Some(next) => next,
+        None => return,
+    };
+
+    root.reseed(next.clone());
+    frame_rng.reseed(&next);
+    tick_rng.reseed(&next);
+
+    let previous = std::mem::replace(&mut *seed, next.clone());
+    changes.send(SeedChanged {
+        previous,
+        seed: next,
+    });
+}
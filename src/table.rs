@@ -0,0 +1,198 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A table of entries, each picked with a probability proportional to its
+/// weight.
+///
+/// ```rust,ignore
+/// let table: WeightedTable<&str> = vec![("common", 10.0), ("rare", 1.0)]
+///     .into_iter()
+///     .collect();
+///
+/// let drop = table.pick(&mut rng);
+/// ```
+///
+/// Entries are always considered in insertion order, so the same rng state
+/// picks the same entry on every platform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedTable<T> {
+    entries: Vec<(T, f32)>,
+    total: f32,
+}
+
+impl<T> Default for WeightedTable<T> {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            total: 0.0,
+        }
+    }
+}
+
+impl<T> WeightedTable<T> {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry with the given weight.
+    ///
+    /// Entries with a weight of zero (or less) are kept, but never picked.
+    pub fn add(&mut self, item: T, weight: f32) -> &mut Self {
+        let weight = weight.max(0.0);
+
+        self.entries.push((item, weight));
+        self.total += weight;
+        self
+    }
+
+    /// The number of entries in the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The sum of all weights.
+    pub fn total_weight(&self) -> f32 {
+        self.total
+    }
+
+    /// Returns the entry at the given index and its weight.
+    pub fn get(&self, index: usize) -> Option<(&T, f32)> {
+        self.entries
+            .get(index)
+            .map(|(item, weight)| (item, *weight))
+    }
+
+    /// Iterate over the entries and their weights, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, f32)> + Clone {
+        self.entries.iter().map(|(item, weight)| (item, *weight))
+    }
+
+    /// Pick a random entry.
+    ///
+    /// Returns `None` if the table has no entry with a positive weight.
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        self.pick_index(rng).map(|index| &self.entries[index].0)
+    }
+
+    /// Pick the index of a random entry.
+    ///
+    /// Returns `None` if the table has no entry with a positive weight.
+    pub fn pick_index<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<usize> {
+        pick_weighted(rng, self.entries.iter().map(|(_, weight)| *weight))
+    }
+
+    /// Pick a random entry, with its weights adjusted by `modifier`.
+    ///
+    /// The table itself is left untouched, which makes this the place to
+    /// apply per-roll effects like a player's luck:
+    ///
+    /// ```rust,ignore
+    /// let luck = 1.1;
+    /// let drop = table.pick_with(&mut rng, &|item: &Item, weight: f32| match item.rarity {
+    ///     Rarity::Rare => weight * luck,
+    ///     _ => weight,
+    /// });
+    /// ```
+    pub fn pick_with<R, M>(&self, rng: &mut R, modifier: &M) -> Option<&T>
+    where
+        R: Rng + ?Sized,
+        M: WeightModifier<T> + ?Sized,
+    {
+        self.pick_index_with(rng, modifier)
+            .map(|index| &self.entries[index].0)
+    }
+
+    /// Pick the index of a random entry, with its weights adjusted by
+    /// `modifier`.
+    pub fn pick_index_with<R, M>(&self, rng: &mut R, modifier: &M) -> Option<usize>
+    where
+        R: Rng + ?Sized,
+        M: WeightModifier<T> + ?Sized,
+    {
+        pick_weighted(
+            rng,
+            self.entries
+                .iter()
+                .map(|(item, weight)| modifier.modify(item, *weight)),
+        )
+    }
+}
+
+/// Adjusts the weights of a [`WeightedTable`] at roll time, see
+/// [`WeightedTable::pick_with`].
+///
+/// This is implemented for closures taking an entry and its weight, for maps
+/// of per-entry multipliers (entries without a multiplier keep their weight),
+/// and for pairs of modifiers, which are applied one after the other.
+pub trait WeightModifier<T> {
+    /// Returns the weight to use for `item`, given its weight in the table.
+    fn modify(&self, item: &T, weight: f32) -> f32;
+}
+
+impl<T, F: Fn(&T, f32) -> f32> WeightModifier<T> for F {
+    fn modify(&self, item: &T, weight: f32) -> f32 {
+        self(item, weight)
+    }
+}
+
+impl<T: Hash + Eq> WeightModifier<T> for HashMap<T, f32> {
+    fn modify(&self, item: &T, weight: f32) -> f32 {
+        weight * self.get(item).copied().unwrap_or(1.0)
+    }
+}
+
+impl<T, A: WeightModifier<T>, B: WeightModifier<T>> WeightModifier<T> for (A, B) {
+    fn modify(&self, item: &T, weight: f32) -> f32 {
+        self.1.modify(item, self.0.modify(item, weight))
+    }
+}
+
+impl<T> FromIterator<(T, f32)> for WeightedTable<T> {
+    fn from_iter<I: IntoIterator<Item = (T, f32)>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for (item, weight) in iter {
+            table.add(item, weight);
+        }
+        table
+    }
+}
+
+/// Pick the index of a weight, with a probability proportional to it.
+///
+/// Negative weights count as zero. Returns `None` if no weight is positive.
+pub(crate) fn pick_weighted<R, I>(rng: &mut R, weights: I) -> Option<usize>
+where
+    R: Rng + ?Sized,
+    I: Iterator<Item = f32> + Clone,
+{
+    let total: f32 = weights.clone().map(|weight| weight.max(0.0)).sum();
+    if total <= 0.0 || !total.is_finite() {
+        return None;
+    }
+
+    let mut target = rng.gen_range(0.0..total);
+    let mut last = None;
+    for (index, weight) in weights.enumerate() {
+        if weight <= 0.0 {
+            continue;
+        }
+
+        if target < weight {
+            return Some(index);
+        }
+
+        target -= weight;
+        last = Some(index);
+    }
+
+    // Rounding errors can leave a sliver of the total unaccounted for.
+    last
+}
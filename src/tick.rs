@@ -0,0 +1,72 @@
+use crate::bevy::prelude::*;
+use crate::{Backend, Rng, Seed};
+use std::ops::{Deref, DerefMut};
+
+/// The `TickRng` resource holds an rng whose stream is derived from the seed
+/// and the current tick number only.
+///
+/// Nothing is carried over from one tick to the next, so the randomness of
+/// tick `N` can be computed without replaying ticks `0..N`, which is what
+/// late-joining clients and replays need. See [`Seed::tick_rng`] to compute
+/// it without the resource.
+///
+/// Advance the tick at the start of each fixed update, either by calling
+/// [`TickRng::advance`] (or [`TickRng::set_tick`]) yourself, or by running the
+/// [`advance_tick_rng`] system first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickRng {
+    tick: u64,
+    rng: Rng,
+}
+
+impl TickRng {
+    pub(crate) fn new(seed: &Seed, backend: Backend) -> Self {
+        Self {
+            tick: 0,
+            rng: seed.tick_rng_with(backend, 0),
+        }
+    }
+
+    /// Switch to a new seed, keeping the current tick.
+    pub(crate) fn reseed(&mut self, seed: &Seed) {
+        self.rng = seed.tick_rng_with(self.rng.backend(), self.tick);
+    }
+
+    /// The current tick.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Jump to the given tick, deriving a fresh stream for it.
+    pub fn set_tick(&mut self, tick: u64) {
+        self.rng = self.rng.seed.tick_rng_with(self.rng.backend(), tick);
+        self.tick = tick;
+    }
+
+    /// Move on to the next tick.
+    pub fn advance(&mut self) {
+        self.set_tick(self.tick.wrapping_add(1));
+    }
+}
+
+impl Deref for TickRng {
+    type Target = Rng;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for TickRng {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+/// Advances the [`TickRng`] resource by one tick.
+///
+/// Add this to your fixed timestep stage, ordered before any system using the
+/// `TickRng`.
+pub fn advance_tick_rng(mut tick_rng: ResMut<TickRng>) {
+    tick_rng.advance();
+}
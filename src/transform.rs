@@ -0,0 +1,50 @@
+use crate::bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Helpers to randomly vary transforms and velocities, e.g. to "spawn with
+/// slight variation".
+///
+/// This is implemented for every `rand::Rng`, including [`Rng`](crate::Rng).
+///
+/// All extents, angles and variations must be non-negative, a zero value
+/// leaves the respective property untouched.
+pub trait RngTransformExt: Rng {
+    /// Move the transform by a random offset within `[-extent, extent]` on
+    /// each axis.
+    fn perturb_translation(&mut self, transform: &mut Transform, extent: Vec3) {
+        transform.translation += Vec3::new(
+            self.gen_range(-extent.x..=extent.x),
+            self.gen_range(-extent.y..=extent.y),
+            self.gen_range(-extent.z..=extent.z),
+        );
+    }
+
+    /// Rotate the transform around a random axis, by a random angle of at
+    /// most `max_angle` radians.
+    fn perturb_rotation(&mut self, transform: &mut Transform, max_angle: f32) {
+        let rotation = Quat::from_axis_angle(self.unit_vector(), self.gen_range(0.0..=max_angle));
+        transform.rotation = rotation * transform.rotation;
+    }
+
+    /// Turn the velocity by a random angle of at most `max_angle` radians, and
+    /// scale its speed by a random factor within
+    /// `[1 - speed_variation, 1 + speed_variation]`.
+    fn jitter_velocity(&mut self, velocity: &mut Vec3, max_angle: f32, speed_variation: f32) {
+        let rotation = Quat::from_axis_angle(self.unit_vector(), self.gen_range(0.0..=max_angle));
+        let speed = self.gen_range(1.0 - speed_variation..=1.0 + speed_variation);
+
+        *velocity = rotation * *velocity * speed;
+    }
+
+    /// Returns a random unit vector, uniformly distributed over the sphere.
+    fn unit_vector(&mut self) -> Vec3 {
+        let z: f32 = self.gen_range(-1.0..=1.0);
+        let angle = self.gen_range(0.0..TAU);
+        let radius = (1.0 - z * z).sqrt();
+
+        Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+    }
+}
+
+impl<R: Rng + ?Sized> RngTransformExt for R {}
@@ -0,0 +1,210 @@
+use crate::bevy::prelude::*;
+use crate::table::pick_weighted;
+use crate::{Rng, RootRng, Seed};
+
+/// The label of the stream the [`WeatherMachine`] draws from, by default.
+pub const WEATHER_STREAM: &str = "weather";
+
+/// The event sent when the state of the [`WeatherMachine`] changes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WeatherChanged {
+    /// The index of the previous state.
+    pub previous: usize,
+
+    /// The index of the new state.
+    pub current: usize,
+
+    /// The name of the new state.
+    pub name: String,
+}
+
+/// The `WeatherMachine` resource simulates the weather with a Markov chain.
+///
+/// Each state has a row of transition weights, one per state: every
+/// `interval` seconds, the next state is picked from the row of the current
+/// one. The weights of a row are relative to each other, so they don't need
+/// to add up to one, and the weight of a state to itself is the chance of
+/// the weather staying the same. A [`WeatherChanged`] event is sent whenever
+/// the state actually changes.
+///
+/// ```rust,ignore
+/// fn setup(mut weather: ResMut<WeatherMachine>) {
+///     let clear = weather.add_state("clear");
+///     let cloudy = weather.add_state("cloudy");
+///     let rain = weather.add_state("rain");
+///
+///     weather
+///         .set_transition(clear, clear, 0.7)
+///         .set_transition(clear, cloudy, 0.3)
+///         .set_transition(cloudy, clear, 0.4)
+///         .set_transition(cloudy, rain, 0.4)
+///         .set_transition(cloudy, cloudy, 0.2)
+///         .set_transition(rain, cloudy, 1.0)
+///         .set_interval(60.0);
+/// }
+/// ```
+///
+/// The machine draws one value from the stream labelled [`WEATHER_STREAM`]
+/// per transition. The timer follows the frame time, so for a reproducible
+/// sequence drive the machine with [`WeatherMachine::step`] from a fixed
+/// timestep instead, and set the interval to zero to disable the timer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeatherMachine {
+    names: Vec<String>,
+    transitions: Vec<Vec<f32>>,
+    current: usize,
+    interval: f32,
+    elapsed: f32,
+    rng: Rng,
+}
+
+impl WeatherMachine {
+    /// Create a machine without states, drawing from the given rng.
+    ///
+    /// The interval defaults to a minute.
+    pub fn new(rng: Rng) -> Self {
+        Self {
+            names: Vec::new(),
+            transitions: Vec::new(),
+            current: 0,
+            interval: 60.0,
+            elapsed: 0.0,
+            rng,
+        }
+    }
+
+    /// Add a state, and return its index. The first state added is the
+    /// initial state.
+    ///
+    /// The new state has no transitions, so the weather stays in it forever
+    /// until some are set.
+    pub fn add_state(&mut self, name: impl Into<String>) -> usize {
+        self.names.push(name.into());
+        for row in &mut self.transitions {
+            row.push(0.0);
+        }
+
+        self.transitions.push(vec![0.0; self.names.len()]);
+        self.names.len() - 1
+    }
+
+    /// Set the weight of the transition from state `from` to state `to`.
+    pub fn set_transition(&mut self, from: usize, to: usize, weight: f32) -> &mut Self {
+        self.transitions[from][to] = weight;
+        self
+    }
+
+    /// Set the time between two transitions, in seconds. An interval of zero
+    /// disables the timer.
+    pub fn set_interval(&mut self, interval: f32) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The time between two transitions, in seconds.
+    pub fn interval(&self) -> f32 {
+        self.interval
+    }
+
+    /// The number of states.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if there are no states.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the index of the state with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|state| state == name)
+    }
+
+    /// Returns the name of the state at the given index.
+    pub fn name(&self, state: usize) -> Option<&str> {
+        self.names.get(state).map(String::as_str)
+    }
+
+    /// The index of the current state.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Force the current state, e.g. for a scripted storm. The timer starts
+    /// over.
+    pub fn set_current(&mut self, state: usize) -> &mut Self {
+        self.current = state;
+        self.elapsed = 0.0;
+        self
+    }
+
+    /// Transition to the next state right away, and return the change if
+    /// the state is different.
+    pub fn step(&mut self) -> Option<WeatherChanged> {
+        let row = self.transitions.get(self.current)?;
+        let next = pick_weighted(&mut self.rng, row.iter().copied())?;
+        if next == self.current {
+            return None;
+        }
+
+        let previous = std::mem::replace(&mut self.current, next);
+        Some(WeatherChanged {
+            previous,
+            current: next,
+            name: self.names[next].clone(),
+        })
+    }
+
+    fn update(&mut self, delta: f32) -> Vec<WeatherChanged> {
+        let mut changes = Vec::new();
+        if self.interval <= 0.0 {
+            return changes;
+        }
+
+        self.elapsed += delta;
+        while self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            changes.extend(self.step());
+        }
+
+        changes
+    }
+}
+
+impl FromWorld for WeatherMachine {
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world.get_resource::<RootRng>() {
+            Some(root) => root.stream_rng(WEATHER_STREAM),
+            None => Seed::from_entropy().stream_rng(WEATHER_STREAM),
+        };
+
+        Self::new(rng)
+    }
+}
+
+/// Adds the [`WeatherMachine`] resource, advances it on its timer, and sends
+/// a [`WeatherChanged`] event for every change.
+///
+/// Add it after the [`RngPlugin`](crate::RngPlugin), so the machine draws
+/// from the seeded stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<WeatherMachine>()
+            .add_event::<WeatherChanged>()
+            .add_system(advance_weather.system());
+    }
+}
+
+fn advance_weather(
+    time: Res<Time>,
+    mut weather: ResMut<WeatherMachine>,
+    mut changes: EventWriter<WeatherChanged>,
+) {
+    for change in weather.update(time.delta_seconds()) {
+        changes.send(change);
+    }
+}
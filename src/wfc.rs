@@ -0,0 +1,447 @@
+use crate::table::pick_weighted;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+use std::error::Error;
+use std::fmt;
+
+/// The maximum number of tiles in a [`TileRules`] set.
+pub const MAX_TILES: usize = 64;
+
+/// A direction on a [`TileGrid`], `x` growing to the right and `y` growing
+/// upwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    /// Towards `y + 1`.
+    Up,
+
+    /// Towards `y - 1`.
+    Down,
+
+    /// Towards `x - 1`.
+    Left,
+
+    /// Towards `x + 1`.
+    Right,
+}
+
+impl Direction {
+    /// All directions.
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
+    /// Returns the opposite direction.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, 1),
+            Self::Down => (0, -1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+        }
+    }
+}
+
+/// The tiles of a [`TileRules::solve`] run, their weights and which tiles may
+/// be placed next to each other.
+///
+/// Tiles are identified by the index returned from [`TileRules::add_tile`].
+///
+/// ```rust,ignore
+/// let mut rules = TileRules::new();
+/// let grass = rules.add_tile(4.0);
+/// let sand = rules.add_tile(1.0);
+/// let water = rules.add_tile(2.0);
+///
+/// rules
+///     .allow_all(grass, grass)
+///     .allow_all(grass, sand)
+///     .allow_all(sand, sand)
+///     .allow_all(sand, water)
+///     .allow_all(water, water);
+///
+/// let grid = rules.solve(&mut *rng, 32, 32, 10)?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileRules {
+    weights: Vec<f32>,
+    allowed: Vec<[u64; 4]>,
+}
+
+impl TileRules {
+    /// Create an empty rule set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tile with the given weight, and return its index.
+    ///
+    /// Tiles with a higher weight are placed more often. Tiles with a weight
+    /// of zero are never placed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule set already has [`MAX_TILES`] tiles.
+    pub fn add_tile(&mut self, weight: f32) -> usize {
+        assert!(self.weights.len() < MAX_TILES, "too many tiles");
+
+        self.weights.push(weight.max(0.0));
+        self.allowed.push([0; 4]);
+        self.weights.len() - 1
+    }
+
+    /// Allow tile `b` to be placed next to tile `a`, in the given direction.
+    ///
+    /// The rule is symmetric: `a` is also allowed next to `b` in the opposite
+    /// direction.
+    pub fn allow(&mut self, a: usize, direction: Direction, b: usize) -> &mut Self {
+        self.allowed[a][direction as usize] |= 1 << b;
+        self.allowed[b][direction.opposite() as usize] |= 1 << a;
+        self
+    }
+
+    /// Allow tiles `a` and `b` next to each other in every direction.
+    pub fn allow_all(&mut self, a: usize, b: usize) -> &mut Self {
+        for direction in Direction::ALL.iter() {
+            self.allow(a, *direction, b);
+        }
+
+        self
+    }
+
+    /// The number of tiles.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Whether the rule set has no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Fill a grid with tiles, respecting the adjacency rules.
+    ///
+    /// The cell with the fewest remaining options is repeatedly collapsed to
+    /// one of them (picked by weight), and the choice is propagated to its
+    /// neighbours. When the rules make a cell impossible to fill, the attempt
+    /// is abandoned, and the solver restarts from a new sub-seed, up to
+    /// `max_attempts` times.
+    ///
+    /// Each attempt draws its sub-seed from `rng`, so the resulting grid only
+    /// depends on the state of `rng`, and `rng` advances by one draw per
+    /// attempt.
+    pub fn solve<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        width: usize,
+        height: usize,
+        max_attempts: u32,
+    ) -> Result<TileGrid, Contradiction> {
+        for _ in 0..max_attempts {
+            let mut attempt = Xoshiro256StarStar::seed_from_u64(rng.next_u64());
+
+            if let Some(grid) = self.attempt(&mut attempt, width, height) {
+                return Ok(grid);
+            }
+        }
+
+        Err(Contradiction {
+            attempts: max_attempts,
+        })
+    }
+
+    fn attempt<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        width: usize,
+        height: usize,
+    ) -> Option<TileGrid> {
+        // Tiles with a weight of zero are never options, so a cell narrowed
+        // down to one option never holds a tile that couldn't be picked.
+        let all = (0..self.len())
+            .filter(|tile| self.weights[*tile] > 0.0)
+            .fold(0, |all, tile| all | 1 << tile);
+        let mut cells = vec![all; width * height];
+        if all == 0 && !cells.is_empty() {
+            return None;
+        }
+
+        // Remove the options the rules forbid before collapsing anything, e.g.
+        // a tile that isn't allowed next to any tile.
+        self.propagate(&mut cells, width, height, (0..width * height).collect())?;
+
+        while let Some(cell) = self.lowest_entropy(rng, &cells) {
+            let options = cells[cell];
+            let weights = (0..self.len()).map(|tile| match options & (1 << tile) {
+                0 => 0.0,
+                _ => self.weights[tile],
+            });
+
+            cells[cell] = 1 << pick_weighted(rng, weights)?;
+            self.propagate(&mut cells, width, height, vec![cell])?;
+        }
+
+        if cells.contains(&0) {
+            return None;
+        }
+
+        let tiles = cells
+            .iter()
+            .map(|options| options.trailing_zeros() as usize)
+            .collect();
+
+        Some(TileGrid {
+            width,
+            height,
+            tiles,
+        })
+    }
+
+    /// Returns an uncollapsed cell with the fewest options, ties broken at
+    /// random.
+    fn lowest_entropy<R: Rng + ?Sized>(&self, rng: &mut R, cells: &[u64]) -> Option<usize> {
+        let mut lowest = None;
+        let mut lowest_count = u32::MAX;
+        let mut ties = 0;
+
+        for (cell, options) in cells.iter().enumerate() {
+            let count = options.count_ones();
+            if count <= 1 || count > lowest_count {
+                continue;
+            }
+
+            if count < lowest_count {
+                lowest_count = count;
+                ties = 0;
+            }
+
+            ties += 1;
+            if rng.gen_range(0..ties) == 0 {
+                lowest = Some(cell);
+            }
+        }
+
+        lowest
+    }
+
+    /// Remove the options that are no longer allowed next to the cells of
+    /// `stack`, and so on, until nothing changes. Returns `None` on a
+    /// contradiction.
+    fn propagate(
+        &self,
+        cells: &mut [u64],
+        width: usize,
+        height: usize,
+        mut stack: Vec<usize>,
+    ) -> Option<()> {
+        while let Some(cell) = stack.pop() {
+            let (x, y) = ((cell % width) as isize, (cell / width) as isize);
+
+            for direction in Direction::ALL.iter() {
+                let (dx, dy) = direction.offset();
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx as usize >= width || ny as usize >= height {
+                    continue;
+                }
+
+                let allowed = (0..self.len())
+                    .filter(|tile| cells[cell] & (1 << tile) != 0)
+                    .fold(0, |allowed, tile| {
+                        allowed | self.allowed[tile][*direction as usize]
+                    });
+
+                let neighbour = ny as usize * width + nx as usize;
+                let options = cells[neighbour] & allowed;
+                if options == 0 {
+                    return None;
+                }
+
+                if options != cells[neighbour] {
+                    cells[neighbour] = options;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// A grid of tiles generated by [`TileRules::solve`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TileGrid {
+    width: usize,
+    height: usize,
+    tiles: Vec<usize>,
+}
+
+impl TileGrid {
+    /// The width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the tile at the given position, if it is within the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.tiles[y * self.width + x])
+    }
+
+    /// Returns all tiles, row by row, starting at `y = 0`.
+    pub fn tiles(&self) -> &[usize] {
+        &self.tiles
+    }
+}
+
+/// The error returned by [`TileRules::solve`] when no attempt produced a
+/// valid grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Contradiction {
+    /// The number of attempts made.
+    pub attempts: u32,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tile rules could not be satisfied in {} attempts",
+            self.attempts
+        )
+    }
+}
+
+impl Error for Contradiction {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    fn rng(seed: u64) -> Xoshiro256StarStar {
+        Xoshiro256StarStar::seed_from_u64(seed)
+    }
+
+    /// Asserts that every pair of neighbours is allowed by the rules.
+    fn assert_valid(rules: &TileRules, grid: &TileGrid) {
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let tile = grid.get(x, y).unwrap();
+                if let Some(right) = grid.get(x + 1, y) {
+                    assert_ne!(
+                        rules.allowed[tile][Direction::Right as usize] & 1 << right,
+                        0
+                    );
+                }
+                if let Some(up) = grid.get(x, y + 1) {
+                    assert_ne!(rules.allowed[tile][Direction::Up as usize] & 1 << up, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn respects_rules() {
+        let mut rules = TileRules::new();
+        let grass = rules.add_tile(4.0);
+        let sand = rules.add_tile(1.0);
+        let water = rules.add_tile(2.0);
+        rules
+            .allow_all(grass, grass)
+            .allow_all(grass, sand)
+            .allow_all(sand, sand)
+            .allow_all(sand, water)
+            .allow_all(water, water);
+
+        for seed in 0..20 {
+            let grid = rules.solve(&mut rng(seed), 16, 12, 10).unwrap();
+            assert_eq!(grid.tiles().len(), 16 * 12);
+            assert_valid(&rules, &grid);
+        }
+    }
+
+    #[test]
+    fn never_places_zero_weight_tiles() {
+        let mut rules = TileRules::new();
+        let a = rules.add_tile(1.0);
+        let b = rules.add_tile(0.0);
+        rules.allow_all(a, a).allow_all(a, b);
+
+        let grid = rules.solve(&mut rng(1), 8, 8, 1).unwrap();
+        assert!(grid.tiles().iter().all(|tile| *tile == a));
+
+        // Without `a` next to `a`, only the zero-weight tile could fill the
+        // neighbours of an `a`.
+        let mut rules = TileRules::new();
+        let a = rules.add_tile(1.0);
+        let b = rules.add_tile(0.0);
+        rules.allow_all(a, b);
+
+        let error = rules.solve(&mut rng(1), 8, 8, 3).unwrap_err();
+        assert_eq!(error.attempts, 3);
+    }
+
+    #[test]
+    fn propagates_the_start_state() {
+        let mut rules = TileRules::new();
+        rules.add_tile(1.0);
+
+        assert!(rules.solve(&mut rng(1), 4, 4, 3).is_err());
+        assert_eq!(rules.solve(&mut rng(1), 1, 1, 1).unwrap().tiles(), &[0]);
+    }
+
+    #[test]
+    fn restarts_from_sub_seeds() {
+        // Neighbours must differ, with three tiles: collapsing cells often
+        // leaves a cell between all three tiles.
+        let mut rules = TileRules::new();
+        let tiles: Vec<_> = (0..3).map(|_| rules.add_tile(1.0)).collect();
+        for a in &tiles {
+            for b in &tiles {
+                if a != b {
+                    rules.allow_all(*a, *b);
+                }
+            }
+        }
+
+        let mut restarted = false;
+        for seed in 0..50 {
+            let mut solved = rng(seed);
+            let grid = rules.solve(&mut solved, 12, 12, 20);
+
+            let mut expected = rng(seed);
+            let mut attempts = 0;
+            let replayed = loop {
+                if attempts == 20 {
+                    break None;
+                }
+
+                attempts += 1;
+                let mut attempt = rng(expected.next_u64());
+                if let Some(grid) = rules.attempt(&mut attempt, 12, 12) {
+                    break Some(grid);
+                }
+            };
+
+            restarted |= attempts > 1;
+            assert_eq!(grid.ok(), replayed);
+            assert_eq!(solved, expected);
+        }
+
+        assert!(restarted, "no attempt failed");
+    }
+}